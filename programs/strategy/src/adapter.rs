@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use user_management::RoleAuthority;
+use vault::cpi::accounts::{DeployToStrategy, RecallFromStrategy};
+use vault::program::Vault as VaultProgram;
+use vault::state::Vault;
+use crate::errors::StrategyError;
+use crate::state::StrategyState;
+
+/// Accounts needed to move the vault's assets between the vault and an
+/// opportunity's adapter.
+///
+/// Each opportunity's adapter is the `[b"adapter", opportunity]` PDA: it holds
+/// the lamports itself for SOL vaults, and owns the
+/// `[b"adapter_token", opportunity]` token account for token vaults. Both
+/// directions go through the vault program, signed by the strategy state PDA,
+/// so the vault can enforce its allocation and keep `deployed_assets` booked.
+/// A SOL adapter also keeps a rent-exempt reserve, funded by the strategy
+/// manager, that is never counted as vault assets.
+pub struct VaultTransfer<'a, 'info> {
+    pub strategy_state: &'a Account<'info, StrategyState>,
+    pub vault: &'a Account<'info, Vault>,
+    pub vault_account: &'a SystemAccount<'info>,
+    pub asset_mint: Option<&'a Account<'info, Mint>>,
+    pub vault_token_account: Option<&'a Account<'info, TokenAccount>>,
+    pub vault_program: &'a Program<'info, VaultProgram>,
+    pub system_program: &'a Program<'info, System>,
+    pub token_program: &'a Program<'info, Token>,
+}
+
+impl<'a, 'info> VaultTransfer<'a, 'info> {
+    /// Have the vault send up to `amount` to `adapter` - the vault caps it by
+    /// its strategy allocation and idle assets, and refuses while the vault
+    /// has rebalancing paused
+    pub fn deploy(
+        &self,
+        role_authority: &Account<'info, RoleAuthority>,
+        adapter: &SystemAccount<'info>,
+        adapter_token_account: Option<&Account<'info, TokenAccount>>,
+        amount: u64,
+    ) -> Result<()> {
+        let strategy_seeds: &[&[u8]] = &[
+            b"strategy",
+            self.strategy_state.vault.as_ref(),
+            &[self.strategy_state.bump],
+        ];
+        vault::cpi::deploy_to_strategy(
+            CpiContext::new_with_signer(
+                self.vault_program.to_account_info(),
+                DeployToStrategy {
+                    vault: self.vault.to_account_info(),
+                    role_authority: role_authority.to_account_info(),
+                    strategy: self.strategy_state.to_account_info(),
+                    adapter: adapter.to_account_info(),
+                    adapter_token_account: adapter_token_account.map(|a| a.to_account_info()),
+                    vault_account: self.vault_account.to_account_info(),
+                    asset_mint: self.asset_mint.map(|a| a.to_account_info()),
+                    vault_token_account: self.vault_token_account.map(|a| a.to_account_info()),
+                    system_program: self.system_program.to_account_info(),
+                    token_program: self.token_program.to_account_info(),
+                },
+                &[strategy_seeds],
+            ),
+            amount,
+        )
+    }
+
+    /// Return `amount` from the adapter of `opportunity` to the vault, signed
+    /// by the adapter PDA; `principal` is the deployed cost it settles
+    pub fn recall(
+        &self,
+        opportunity: &Pubkey,
+        adapter: &SystemAccount<'info>,
+        adapter_bump: u8,
+        adapter_token_account: Option<&Account<'info, TokenAccount>>,
+        amount: u64,
+        principal: u64,
+    ) -> Result<()> {
+        let strategy_seeds: &[&[u8]] = &[
+            b"strategy",
+            self.strategy_state.vault.as_ref(),
+            &[self.strategy_state.bump],
+        ];
+        let adapter_seeds: &[&[u8]] = &[b"adapter", opportunity.as_ref(), &[adapter_bump]];
+        vault::cpi::recall_from_strategy(
+            CpiContext::new_with_signer(
+                self.vault_program.to_account_info(),
+                RecallFromStrategy {
+                    vault: self.vault.to_account_info(),
+                    strategy: self.strategy_state.to_account_info(),
+                    adapter: adapter.to_account_info(),
+                    adapter_token_account: adapter_token_account.map(|a| a.to_account_info()),
+                    vault_account: self.vault_account.to_account_info(),
+                    asset_mint: self.asset_mint.map(|a| a.to_account_info()),
+                    vault_token_account: self.vault_token_account.map(|a| a.to_account_info()),
+                    system_program: self.system_program.to_account_info(),
+                    token_program: self.token_program.to_account_info(),
+                },
+                &[strategy_seeds, adapter_seeds],
+            ),
+            amount,
+            principal,
+        )
+    }
+}
+
+/// Assets an adapter currently holds for the vault: its lamports above the
+/// rent reserve for SOL vaults, its token balance for token vaults
+pub fn adapter_balance(
+    vault: &Vault,
+    adapter: &SystemAccount,
+    adapter_token_account: Option<&Account<TokenAccount>>,
+) -> Result<u64> {
+    if vault.is_native() {
+        let rent_reserve = Rent::get()?.minimum_balance(0);
+        return Ok(adapter.lamports().saturating_sub(rent_reserve));
+    }
+    Ok(adapter_token_account
+        .ok_or(StrategyError::MissingTokenAccount)?
+        .amount)
+}
+
+/// Top a SOL vault's adapter up to its rent-exempt reserve, paid by `payer`,
+/// before the vault deploys to it - so neither a small deployment nor a
+/// partial recall can leave the adapter below the rent-exempt minimum
+pub fn fund_adapter_rent<'info>(
+    vault: &Vault,
+    adapter: &SystemAccount<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if !vault.is_native() {
+        return Ok(());
+    }
+    let missing = Rent::get()?
+        .minimum_balance(0)
+        .saturating_sub(adapter.lamports());
+    if missing == 0 {
+        return Ok(());
+    }
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: payer.to_account_info(),
+                to: adapter.to_account_info(),
+            },
+        ),
+        missing,
+    )
+}
@@ -35,9 +35,33 @@ pub enum StrategyError {
     #[msg("RBAC: Invalid role value")]
     InvalidRole,
 
-    #[msg("RBAC: System is under emergency pause")]
+    #[msg("RBAC: Operation is paused")]
     EmergencyPaused,
 
     #[msg("RBAC: Role authority not found")]
     RoleAuthorityNotFound,
+
+    #[msg("Protocol is already registered with this strategy")]
+    DuplicateProtocol,
+
+    #[msg("Strategy has reached the maximum number of opportunities")]
+    TooManyOpportunities,
+
+    #[msg("Adapter token accounts are required for SPL-token vaults")]
+    MissingTokenAccount,
+
+    #[msg("Invalid allocation settings")]
+    InvalidAllocation,
+
+    #[msg("Weighted allocation is active - use rebalance_weighted")]
+    WeightedAllocationActive,
+
+    #[msg("Recall deployed capital before switching allocation mode")]
+    CapitalDeployed,
+
+    #[msg("Position is within its drift threshold of the target")]
+    WithinDrift,
+
+    #[msg("Withdraw queue is already covered by the vault's idle assets")]
+    NoWithdrawShortfall,
 }
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use user_management::{RoleAuthority, UserRole, PAUSE_ADMIN, ROLE_ADMIN};
+use crate::state::{AllocationConfig, StrategyState};
+use crate::errors::StrategyError;
+
+/// Set the weighted allocation settings (Admin role of the vault)
+///
+/// `top_n > 0` switches rebalancing to `rebalance_weighted`, spreading capital
+/// across the top-scoring opportunities once `update_targets` has filled the
+/// target table. `top_n == 0` returns to winner-take-all `rebalance`. Each
+/// mode tracks its positions its own way, so switching in either direction
+/// needs all deployed capital recalled first.
+pub fn configure_allocation(ctx: Context<ConfigureAllocation>, config: AllocationConfig) -> Result<()> {
+    config.validate()?;
+
+    let strategy = &mut ctx.accounts.strategy_state;
+    if config.is_weighted() != strategy.is_weighted() {
+        if strategy.deployed_value > 0 {
+            return Err(StrategyError::CapitalDeployed.into());
+        }
+        strategy.targets.clear();
+        strategy.current_opportunity = Pubkey::default();
+    }
+    strategy.allocation = config;
+
+    msg!(
+        "Allocation: top {} opportunities, weights {}-{}%, drift {}%",
+        config.top_n,
+        config.min_weight,
+        config.max_weight,
+        config.drift_threshold
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ConfigureAllocation<'info> {
+    /// Strategy state
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_state.vault.as_ref()],
+        bump = strategy_state.bump
+    )]
+    pub strategy_state: Account<'info, StrategyState>,
+
+    /// Admin signer
+    pub admin: Signer<'info>,
+
+    /// Admin's role for the vault (must hold Admin)
+    #[account(
+        seeds = [b"user_role", strategy_state.vault.as_ref(), admin.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = admin_role.bump,
+        constraint = admin_role.has_role(ROLE_ADMIN) @ StrategyError::Unauthorized
+    )]
+    pub admin_role: Account<'info, UserRole>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", strategy_state.vault.as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_ADMIN) @ StrategyError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+}
@@ -1,16 +1,30 @@
 use anchor_lang::prelude::*;
+use user_management::{RoleAuthority, UserRole, PAUSE_REBALANCE, ROLE_STRATEGY_MANAGER};
 use crate::state::{Opportunity, StrategyState};
 use crate::scoring::ScoringEngine;
+use crate::errors::StrategyError;
 
-pub fn evaluate(
-    ctx: Context<Evaluate>,
+/// Update an opportunity's metrics and keep the strategy's best opportunity
+/// current
+///
+/// A challenger is compared against `current_best`. When the current best
+/// itself is re-scored lower, every other registered opportunity must be
+/// passed as remaining accounts, in registry order, so the new best can be
+/// recomputed on-chain.
+pub fn evaluate<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Evaluate<'info>>,
     new_apy: u16,
     new_volatility: u8,
     new_il_risk: u8,
     new_safety: u8,
 ) -> Result<()> {
+    if new_volatility > 100 || new_il_risk > 100 || new_safety > 100 {
+        return Err(StrategyError::InvalidOpportunity.into());
+    }
+
+    let strategy_key = ctx.accounts.strategy_state.key();
+    let opportunity_key = ctx.accounts.opportunity.key();
     let opportunity = &mut ctx.accounts.opportunity;
-    let old_score = opportunity.score;
 
     // Update opportunity data
     opportunity.apy = new_apy;
@@ -28,31 +42,106 @@ pub fn evaluate(
     opportunity.score = new_score;
     opportunity.last_updated = Clock::get()?.unix_timestamp;
 
-    // Update strategy state if this is now the best
     let strategy = &mut ctx.accounts.strategy_state;
-    if new_score > ctx.accounts.current_best.score {
-        strategy.best_opportunity = ctx.accounts.opportunity_index;
+    if opportunity_key == strategy.best_opportunity {
+        let previous_score = strategy.best_score;
+        strategy.best_score = new_score;
+
+        // The current best can only lose its place when its own score drops
+        if new_score < previous_score {
+            let candidates = active_others(
+                &strategy_key,
+                strategy.num_opportunities,
+                opportunity.index,
+                ctx.remaining_accounts,
+            )?;
+            let (best_key, best_score) =
+                ScoringEngine::rescan_best((opportunity_key, new_score), &candidates);
+            strategy.best_opportunity = best_key;
+            strategy.best_score = best_score;
+        }
+    } else {
+        // A challenger takes over only by beating the current best
+        let current_best = ctx
+            .accounts
+            .current_best
+            .as_ref()
+            .ok_or(StrategyError::InvalidOpportunity)?;
+        if opportunity.active && new_score > current_best.score {
+            strategy.best_opportunity = opportunity_key;
+            strategy.best_score = new_score;
+        }
     }
 
     Ok(())
 }
 
+/// Keys and scores of the active opportunities among `others`, which must be
+/// every opportunity registered with the strategy except the one at `skip`,
+/// in registry order (at most `MAX_OPPORTUNITIES - 1` accounts)
+fn active_others<'info>(
+    strategy: &Pubkey,
+    num_opportunities: u8,
+    skip: u8,
+    others: &'info [AccountInfo<'info>],
+) -> Result<Vec<(Pubkey, u16)>> {
+    if others.len() + 1 != num_opportunities as usize {
+        return Err(StrategyError::InvalidOpportunity.into());
+    }
+
+    let indices = (0..num_opportunities).filter(|index| *index != skip);
+    let mut candidates = Vec::with_capacity(others.len());
+    for (account, index) in others.iter().zip(indices) {
+        let opportunity = Opportunity::load_registered(account, strategy, index)?;
+        if opportunity.active {
+            candidates.push((opportunity.key(), opportunity.score));
+        }
+    }
+
+    Ok(candidates)
+}
+
 #[derive(Accounts)]
 pub struct Evaluate<'info> {
     /// Strategy state
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_state.vault.as_ref()],
+        bump = strategy_state.bump
+    )]
     pub strategy_state: Account<'info, StrategyState>,
 
     /// Opportunity to evaluate
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"opportunity", strategy_state.key().as_ref(), &[opportunity.index]],
+        bump = opportunity.bump
+    )]
     pub opportunity: Account<'info, Opportunity>,
 
-    /// Current best opportunity (for comparison)
-    pub current_best: Account<'info, Opportunity>,
+    /// Strategy's current best opportunity, for comparison (omitted when
+    /// evaluating the best itself)
+    #[account(address = strategy_state.best_opportunity @ StrategyError::InvalidOpportunity)]
+    pub current_best: Option<Account<'info, Opportunity>>,
+
+    /// Strategy manager signer
+    pub manager: Signer<'info>,
 
-    /// Index of the opportunity being evaluated
-    pub opportunity_index: u8,
+    /// Manager's role for the vault (must hold StrategyManager)
+    #[account(
+        seeds = [b"user_role", strategy_state.vault.as_ref(), manager.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = manager_role.bump,
+        constraint = manager_role.has_role(ROLE_STRATEGY_MANAGER) @ StrategyError::Unauthorized
+    )]
+    pub manager_role: Account<'info, UserRole>,
 
-    /// Admin signer
-    pub admin: Signer<'info>,
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", strategy_state.vault.as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_REBALANCE) @ StrategyError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
 }
@@ -1,16 +1,26 @@
 use anchor_lang::prelude::*;
-use crate::state::StrategyState;
+use user_management::{UserRole, ROLE_ADMIN};
+use vault::state::Vault;
+use crate::state::{AllocationConfig, StrategyState};
+use crate::errors::StrategyError;
 
+/// Create the strategy engine for a vault (the vault's admin, holding the
+/// Admin role) - the state is seeded by the vault key, so each vault has
+/// exactly one
 pub fn initialize(ctx: Context<Initialize>, rebalance_threshold: u16) -> Result<()> {
     let strategy_state = &mut ctx.accounts.strategy_state;
 
     strategy_state.vault = ctx.accounts.vault.key();
-    strategy_state.best_opportunity = 0;
+    strategy_state.best_opportunity = Pubkey::default();
+    strategy_state.best_score = 0;
     strategy_state.num_opportunities = 0;
     strategy_state.rebalance_threshold = rebalance_threshold;
     strategy_state.last_rebalance = Clock::get()?.unix_timestamp;
+    strategy_state.current_opportunity = Pubkey::default();
     strategy_state.deployed_value = 0;
     strategy_state.bump = ctx.bumps.strategy_state;
+    strategy_state.allocation = AllocationConfig::default();
+    strategy_state.targets = Vec::new();
 
     Ok(())
 }
@@ -18,16 +28,32 @@ pub fn initialize(ctx: Context<Initialize>, rebalance_threshold: u16) -> Result<
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     /// Strategy state to initialize
-    #[account(init, payer = admin, space = StrategyState::LEN)]
+    #[account(
+        init,
+        payer = admin,
+        space = StrategyState::LEN,
+        seeds = [b"strategy", vault.key().as_ref()],
+        bump
+    )]
     pub strategy_state: Account<'info, StrategyState>,
 
-    /// Vault this strategy serves
-    pub vault: AccountInfo<'info>,
+    /// Vault this strategy serves (owned by the vault program)
+    #[account(has_one = admin @ StrategyError::Unauthorized)]
+    pub vault: Account<'info, Vault>,
 
-    /// Admin signer
+    /// Admin signer (the vault's admin)
     #[account(mut)]
     pub admin: Signer<'info>,
 
+    /// Admin's role for the vault (must hold Admin)
+    #[account(
+        seeds = [b"user_role", vault.key().as_ref(), admin.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = admin_role.bump,
+        constraint = admin_role.has_role(ROLE_ADMIN) @ StrategyError::Unauthorized
+    )]
+    pub admin_role: Account<'info, UserRole>,
+
     /// System program
     pub system_program: Program<'info, System>,
 }
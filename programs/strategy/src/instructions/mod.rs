@@ -2,8 +2,18 @@ pub mod initialize;
 pub mod register_opportunity;
 pub mod evaluate;
 pub mod rebalance;
+pub mod recall;
+pub mod configure_allocation;
+pub mod update_targets;
+pub mod rebalance_weighted;
+pub mod unwind;
 
 pub use initialize::*;
 pub use register_opportunity::*;
 pub use evaluate::*;
 pub use rebalance::*;
+pub use recall::*;
+pub use configure_allocation::*;
+pub use update_targets::*;
+pub use rebalance_weighted::*;
+pub use unwind::*;
@@ -1,13 +1,27 @@
 use anchor_lang::prelude::*;
-use crate::state::StrategyState;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use user_management::{RoleAuthority, UserRole, PAUSE_REBALANCE, ROLE_STRATEGY_MANAGER};
+use vault::program::Vault as VaultProgram;
+use vault::state::Vault;
+use crate::adapter::{adapter_balance, fund_adapter_rent, VaultTransfer};
+use crate::state::{DeployedPosition, Opportunity, StrategyState, REBALANCE_COOLDOWN};
 use crate::scoring::ScoringEngine;
 use crate::errors::StrategyError;
 
-const REBALANCE_COOLDOWN: i64 = 3600; // 1 hour minimum between rebalances
-
+/// Move the deployed assets to the best opportunity (StrategyManager role)
+///
+/// If the assets sit in another opportunity that the best one beats by more
+/// than the rebalance threshold, its whole adapter balance is recalled to the
+/// vault. The vault then deploys as much as its strategy allocation and idle
+/// assets allow to the best opportunity's adapter. With nothing deployed yet,
+/// or with the best opportunity already holding the assets, only the deploy
+/// step runs, topping the position up.
 pub fn rebalance(ctx: Context<Rebalance>) -> Result<()> {
-    let strategy = &mut ctx.accounts.strategy_state;
     let current_time = Clock::get()?.unix_timestamp;
+    let strategy = &ctx.accounts.strategy_state;
+    if strategy.is_weighted() {
+        return Err(StrategyError::WeightedAllocationActive.into());
+    }
 
     // Check cooldown
     let time_since_last = current_time.checked_sub(strategy.last_rebalance)
@@ -17,26 +31,106 @@ pub fn rebalance(ctx: Context<Rebalance>) -> Result<()> {
         return Err(StrategyError::RebalanceCooldown.into());
     }
 
-    // Check if rebalancing is needed
-    let current_score = ctx.accounts.current_best.score;
-    let best_score = ctx.accounts.best_opportunity.score;
-    let should_rebalance = ScoringEngine::should_rebalance(
-        current_score,
-        best_score,
-        strategy.rebalance_threshold,
-    );
-
-    if !should_rebalance {
-        return Err(StrategyError::ScoreTooLow.into());
+    let best = &ctx.accounts.best_opportunity;
+    if !best.active {
+        return Err(StrategyError::InvalidOpportunity.into());
+    }
+
+    let current_key = strategy.current_opportunity;
+    if current_key != Pubkey::default() && current_key != best.key() {
+        // Check if rebalancing is needed
+        let current = ctx
+            .accounts
+            .current_opportunity
+            .as_ref()
+            .ok_or(StrategyError::InvalidOpportunity)?;
+        // A deactivated opportunity is always worth leaving
+        let current_score = if current.active { current.score } else { 0 };
+        let should_rebalance = ScoringEngine::should_rebalance(
+            current_score,
+            best.score,
+            strategy.rebalance_threshold,
+        );
+
+        if !should_rebalance {
+            return Err(StrategyError::ScoreTooLow.into());
+        }
+
+        msg!("Rebalancing: moving from score {} to score {}", current_score, best.score);
+        recall_current(ctx.accounts, current_key)?;
+        ctx.accounts.vault.reload()?;
     }
 
-    // Update strategy state
+    // The vault caps the deployment by its allocation and idle assets
+    fund_adapter_rent(
+        &ctx.accounts.vault,
+        &ctx.accounts.best_adapter,
+        &ctx.accounts.manager,
+        &ctx.accounts.system_program,
+    )?;
+    let deployed_before = ctx.accounts.vault.deployed_assets;
+    ctx.accounts.vault_transfer().deploy(
+        &ctx.accounts.role_authority,
+        &ctx.accounts.best_adapter,
+        ctx.accounts.best_adapter_token.as_ref(),
+        u64::MAX,
+    )?;
+    ctx.accounts.vault.reload()?;
+    let deployed = ctx.accounts.vault.deployed_assets.saturating_sub(deployed_before);
+
+    let best_key = ctx.accounts.best_opportunity.key();
+    let position = &mut ctx.accounts.best_position;
+    position.opportunity = best_key;
+    position.amount = position.amount.checked_add(deployed)
+        .ok_or(StrategyError::OverflowError)?;
+    position.deployment_timestamp = current_time;
+    position.bump = ctx.bumps.best_position;
+    position.adapter_bump = ctx.bumps.best_adapter;
+
+    let strategy = &mut ctx.accounts.strategy_state;
+    strategy.current_opportunity = best_key;
+    strategy.deployed_value = strategy.deployed_value.checked_add(deployed)
+        .ok_or(StrategyError::OverflowError)?;
     strategy.last_rebalance = current_time;
 
-    // Note: Actual fund movement would be done via CPI calls to vault/protocol programs
-    // For MVP, we just emit the rebalancing decision
+    msg!("Deployed {} to the best opportunity, {} in position", deployed, position.amount);
+
+    Ok(())
+}
+
+/// Recall the current opportunity's whole adapter balance to the vault and
+/// close out its position: anything above the principal is booked as yield,
+/// any shortfall is realized by the vault as a loss.
+fn recall_current(accounts: &mut Rebalance, current_key: Pubkey) -> Result<()> {
+    let position = accounts
+        .current_position
+        .as_ref()
+        .ok_or(StrategyError::InvalidOpportunity)?;
+    let adapter = accounts
+        .current_adapter
+        .as_ref()
+        .ok_or(StrategyError::InvalidOpportunity)?;
+    let balance = adapter_balance(&accounts.vault, adapter, accounts.current_adapter_token.as_ref())?;
+    let principal = position.amount;
+    if balance > 0 || principal > 0 {
+        accounts.vault_transfer().recall(
+            &current_key,
+            adapter,
+            position.adapter_bump,
+            accounts.current_adapter_token.as_ref(),
+            balance,
+            principal,
+        )?;
+    }
+
+    let position = accounts
+        .current_position
+        .as_mut()
+        .ok_or(StrategyError::InvalidOpportunity)?;
+    position.record_exit(balance)?;
 
-    msg!("Rebalancing triggered: Moving from score {} to score {}", current_score, best_score);
+    let strategy = &mut accounts.strategy_state;
+    strategy.deployed_value = strategy.deployed_value.saturating_sub(principal);
 
     Ok(())
 }
@@ -44,15 +138,137 @@ pub fn rebalance(ctx: Context<Rebalance>) -> Result<()> {
 #[derive(Accounts)]
 pub struct Rebalance<'info> {
     /// Strategy state
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_state.vault.as_ref()],
+        bump = strategy_state.bump
+    )]
     pub strategy_state: Account<'info, StrategyState>,
 
-    /// Current best opportunity
-    pub current_best: AccountInfo<'info>,
+    /// Opportunity the strategy's assets are in now (omit while nothing is
+    /// deployed or when it is the best opportunity)
+    #[account(address = strategy_state.current_opportunity @ StrategyError::InvalidOpportunity)]
+    pub current_opportunity: Option<Account<'info, Opportunity>>,
+
+    /// Position in the current opportunity
+    #[account(
+        mut,
+        seeds = [b"position", strategy_state.current_opportunity.as_ref()],
+        bump = current_position.bump
+    )]
+    pub current_position: Option<Account<'info, DeployedPosition>>,
 
-    /// New best opportunity
-    pub best_opportunity: AccountInfo<'info>,
+    /// Current opportunity's adapter
+    #[account(
+        mut,
+        seeds = [b"adapter", strategy_state.current_opportunity.as_ref()],
+        bump
+    )]
+    pub current_adapter: Option<SystemAccount<'info>>,
 
-    /// Admin signer
-    pub admin: Signer<'info>,
+    /// Current opportunity's adapter token account (token vaults only)
+    #[account(
+        mut,
+        seeds = [b"adapter_token", strategy_state.current_opportunity.as_ref()],
+        bump
+    )]
+    pub current_adapter_token: Option<Account<'info, TokenAccount>>,
+
+    /// Strategy's best opportunity
+    #[account(address = strategy_state.best_opportunity @ StrategyError::InvalidOpportunity)]
+    pub best_opportunity: Account<'info, Opportunity>,
+
+    /// Position in the best opportunity (created on first deployment)
+    #[account(
+        init_if_needed,
+        payer = manager,
+        space = DeployedPosition::LEN,
+        seeds = [b"position", best_opportunity.key().as_ref()],
+        bump
+    )]
+    pub best_position: Account<'info, DeployedPosition>,
+
+    /// Best opportunity's adapter
+    #[account(
+        mut,
+        seeds = [b"adapter", best_opportunity.key().as_ref()],
+        bump
+    )]
+    pub best_adapter: SystemAccount<'info>,
+
+    /// Best opportunity's adapter token account (token vaults only, created
+    /// on first deployment)
+    #[account(
+        init_if_needed,
+        payer = manager,
+        seeds = [b"adapter_token", best_opportunity.key().as_ref()],
+        bump,
+        token::mint = asset_mint,
+        token::authority = best_adapter
+    )]
+    pub best_adapter_token: Option<Account<'info, TokenAccount>>,
+
+    /// Vault the strategy manages
+    #[account(mut, address = strategy_state.vault)]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault SOL account (checked by the vault program)
+    #[account(mut)]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only,
+    /// checked by the vault program)
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Strategy manager signer (pays for new position accounts and the SOL
+    /// adapter's rent reserve)
+    #[account(mut)]
+    pub manager: Signer<'info>,
+
+    /// Manager's role for the vault (must hold StrategyManager)
+    #[account(
+        seeds = [b"user_role", strategy_state.vault.as_ref(), manager.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = manager_role.bump,
+        constraint = manager_role.has_role(ROLE_STRATEGY_MANAGER) @ StrategyError::Unauthorized
+    )]
+    pub manager_role: Account<'info, UserRole>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", strategy_state.vault.as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_REBALANCE) @ StrategyError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
+    /// Vault program
+    pub vault_program: Program<'info, VaultProgram>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Rebalance<'info> {
+    pub fn vault_transfer(&self) -> VaultTransfer<'_, 'info> {
+        VaultTransfer {
+            strategy_state: &self.strategy_state,
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            vault_program: &self.vault_program,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
 }
@@ -0,0 +1,231 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use user_management::{RoleAuthority, UserRole, PAUSE_REBALANCE, ROLE_STRATEGY_MANAGER};
+use vault::program::Vault as VaultProgram;
+use vault::state::Vault;
+use crate::adapter::{adapter_balance, fund_adapter_rent, VaultTransfer};
+use crate::state::{DeployedPosition, Opportunity, StrategyState, REBALANCE_COOLDOWN};
+use crate::errors::StrategyError;
+
+/// Move one opportunity's position toward its target weight (StrategyManager role)
+///
+/// The strategy's capital is the most the vault lets it deploy
+/// (`strategy_allocation` percent of the vault's assets). An opportunity's
+/// target is its weight in the target table times that capital - 0 if it is
+/// not in the table or no longer active. Positions within the drift
+/// threshold of their target are left alone, so a keeper can call this for
+/// every opportunity holding or targeting capital and only the necessary
+/// moves happen. Each position moves at most once per `REBALANCE_COOLDOWN`.
+/// Run the recalls first, so the deploys have idle assets.
+pub fn rebalance_weighted(ctx: Context<RebalanceWeighted>) -> Result<()> {
+    let strategy = &ctx.accounts.strategy_state;
+    if !strategy.is_weighted() || strategy.targets.is_empty() {
+        return Err(StrategyError::InvalidAllocation.into());
+    }
+
+    let opportunity = &ctx.accounts.opportunity;
+    let weight = if opportunity.active {
+        strategy.target_weight(&opportunity.key())
+    } else {
+        0
+    };
+    let capital = ctx.accounts.vault.strategy_limit();
+    let target = percent_of(capital, weight);
+    let current = ctx.accounts.position.amount;
+    let drift = current.abs_diff(target);
+    if drift == 0 || drift <= percent_of(capital, strategy.allocation.drift_threshold) {
+        return Err(StrategyError::WithinDrift.into());
+    }
+
+    let current_time = Clock::get()?.unix_timestamp;
+    let time_since_last = current_time
+        .checked_sub(ctx.accounts.position.deployment_timestamp)
+        .ok_or(StrategyError::OverflowError)?;
+    if time_since_last < REBALANCE_COOLDOWN {
+        return Err(StrategyError::RebalanceCooldown.into());
+    }
+
+    let opportunity_key = opportunity.key();
+    let position = &mut ctx.accounts.position;
+    position.deployment_timestamp = current_time;
+    position.opportunity = opportunity_key;
+    position.bump = ctx.bumps.position;
+    position.adapter_bump = ctx.bumps.adapter;
+
+    if current > target {
+        let balance = adapter_balance(
+            &ctx.accounts.vault,
+            &ctx.accounts.adapter,
+            ctx.accounts.adapter_token.as_ref(),
+        )?;
+        let position = &mut ctx.accounts.position;
+        let (recalled, principal) = if target == 0 {
+            // Leaving the opportunity: bring home its yield too; the vault
+            // realizes any shortfall as a loss
+            (balance, position.record_exit(balance)?)
+        } else {
+            let recalled = (current - target).min(balance);
+            (recalled, position.record_recall(recalled)?)
+        };
+
+        ctx.accounts.vault_transfer().recall(
+            &opportunity_key,
+            &ctx.accounts.adapter,
+            ctx.accounts.position.adapter_bump,
+            ctx.accounts.adapter_token.as_ref(),
+            recalled,
+            principal,
+        )?;
+
+        let strategy = &mut ctx.accounts.strategy_state;
+        strategy.deployed_value = strategy.deployed_value.saturating_sub(principal);
+        msg!("Recalled {} toward a {}% target", recalled, weight);
+    } else {
+        // The vault caps the deployment by its allocation and idle assets
+        fund_adapter_rent(
+            &ctx.accounts.vault,
+            &ctx.accounts.adapter,
+            &ctx.accounts.manager,
+            &ctx.accounts.system_program,
+        )?;
+        let deployed_before = ctx.accounts.vault.deployed_assets;
+        ctx.accounts.vault_transfer().deploy(
+            &ctx.accounts.role_authority,
+            &ctx.accounts.adapter,
+            ctx.accounts.adapter_token.as_ref(),
+            target - current,
+        )?;
+        ctx.accounts.vault.reload()?;
+        let deployed = ctx.accounts.vault.deployed_assets.saturating_sub(deployed_before);
+
+        let position = &mut ctx.accounts.position;
+        position.amount = position.amount.checked_add(deployed)
+            .ok_or(StrategyError::OverflowError)?;
+
+        let strategy = &mut ctx.accounts.strategy_state;
+        strategy.deployed_value = strategy.deployed_value.checked_add(deployed)
+            .ok_or(StrategyError::OverflowError)?;
+        msg!("Deployed {} toward a {}% target", deployed, weight);
+    }
+
+    ctx.accounts.strategy_state.last_rebalance = current_time;
+
+    Ok(())
+}
+
+/// `percent` percent of `amount`, rounded down
+fn percent_of(amount: u64, percent: u8) -> u64 {
+    (amount as u128 * percent as u128 / 100) as u64
+}
+
+#[derive(Accounts)]
+pub struct RebalanceWeighted<'info> {
+    /// Strategy state
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_state.vault.as_ref()],
+        bump = strategy_state.bump
+    )]
+    pub strategy_state: Account<'info, StrategyState>,
+
+    /// Opportunity to move toward its target
+    #[account(
+        seeds = [b"opportunity", strategy_state.key().as_ref(), &[opportunity.index]],
+        bump = opportunity.bump
+    )]
+    pub opportunity: Account<'info, Opportunity>,
+
+    /// Position in the opportunity (created on first deployment)
+    #[account(
+        init_if_needed,
+        payer = manager,
+        space = DeployedPosition::LEN,
+        seeds = [b"position", opportunity.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, DeployedPosition>,
+
+    /// Opportunity's adapter
+    #[account(
+        mut,
+        seeds = [b"adapter", opportunity.key().as_ref()],
+        bump
+    )]
+    pub adapter: SystemAccount<'info>,
+
+    /// Opportunity's adapter token account (token vaults only, created on
+    /// first deployment)
+    #[account(
+        init_if_needed,
+        payer = manager,
+        seeds = [b"adapter_token", opportunity.key().as_ref()],
+        bump,
+        token::mint = asset_mint,
+        token::authority = adapter
+    )]
+    pub adapter_token: Option<Account<'info, TokenAccount>>,
+
+    /// Vault the strategy manages
+    #[account(mut, address = strategy_state.vault)]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault SOL account (checked by the vault program)
+    #[account(mut)]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only,
+    /// checked by the vault program)
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Strategy manager signer (pays for new position accounts and the SOL
+    /// adapter's rent reserve)
+    #[account(mut)]
+    pub manager: Signer<'info>,
+
+    /// Manager's role for the vault (must hold StrategyManager)
+    #[account(
+        seeds = [b"user_role", strategy_state.vault.as_ref(), manager.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = manager_role.bump,
+        constraint = manager_role.has_role(ROLE_STRATEGY_MANAGER) @ StrategyError::Unauthorized
+    )]
+    pub manager_role: Account<'info, UserRole>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", strategy_state.vault.as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_REBALANCE) @ StrategyError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
+    /// Vault program
+    pub vault_program: Program<'info, VaultProgram>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> RebalanceWeighted<'info> {
+    pub fn vault_transfer(&self) -> VaultTransfer<'_, 'info> {
+        VaultTransfer {
+            strategy_state: &self.strategy_state,
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            vault_program: &self.vault_program,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
+}
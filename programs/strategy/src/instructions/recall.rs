@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use user_management::{UserRole, ROLE_STRATEGY_MANAGER};
+use vault::program::Vault as VaultProgram;
+use vault::state::Vault;
+use crate::adapter::{adapter_balance, VaultTransfer};
+use crate::state::{DeployedPosition, Opportunity, StrategyState};
+use crate::errors::StrategyError;
+
+/// Return up to `amount` from an opportunity's position to the vault
+/// (StrategyManager role)
+///
+/// Used to unwind the strategy, e.g. to free assets for the vault's withdraw
+/// queue, after its strategy allocation is lowered, or before switching
+/// allocation mode. Principal comes back first; anything recalled beyond it
+/// is booked as the position's yield. Never paused, so assets can always be
+/// brought home.
+pub fn recall(ctx: Context<Recall>, amount: u64) -> Result<()> {
+    let balance = adapter_balance(
+        &ctx.accounts.vault,
+        &ctx.accounts.adapter,
+        ctx.accounts.adapter_token.as_ref(),
+    )?;
+    let recalled = amount.min(balance);
+    if recalled == 0 {
+        return Err(StrategyError::InsufficientDeployed.into());
+    }
+
+    let position = &mut ctx.accounts.position;
+    let principal = position.record_recall(recalled)?;
+    ctx.accounts.vault_transfer().recall(
+        &ctx.accounts.opportunity.key(),
+        &ctx.accounts.adapter,
+        ctx.accounts.position.adapter_bump,
+        ctx.accounts.adapter_token.as_ref(),
+        recalled,
+        principal,
+    )?;
+
+    let position = &ctx.accounts.position;
+    let strategy = &mut ctx.accounts.strategy_state;
+    strategy.deployed_value = strategy.deployed_value.saturating_sub(principal);
+
+    msg!("Recalled {} to the vault, {} still in position", recalled, position.amount);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Recall<'info> {
+    /// Strategy state
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_state.vault.as_ref()],
+        bump = strategy_state.bump
+    )]
+    pub strategy_state: Account<'info, StrategyState>,
+
+    /// Opportunity to recall from
+    #[account(
+        seeds = [b"opportunity", strategy_state.key().as_ref(), &[opportunity.index]],
+        bump = opportunity.bump
+    )]
+    pub opportunity: Account<'info, Opportunity>,
+
+    /// Position in the opportunity
+    #[account(
+        mut,
+        seeds = [b"position", opportunity.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, DeployedPosition>,
+
+    /// Opportunity's adapter
+    #[account(
+        mut,
+        seeds = [b"adapter", opportunity.key().as_ref()],
+        bump = position.adapter_bump
+    )]
+    pub adapter: SystemAccount<'info>,
+
+    /// Opportunity's adapter token account (token vaults only)
+    #[account(
+        mut,
+        seeds = [b"adapter_token", opportunity.key().as_ref()],
+        bump
+    )]
+    pub adapter_token: Option<Account<'info, TokenAccount>>,
+
+    /// Vault the strategy manages
+    #[account(mut, address = strategy_state.vault)]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault SOL account (checked by the vault program)
+    #[account(mut)]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only,
+    /// checked by the vault program)
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Strategy manager signer
+    pub manager: Signer<'info>,
+
+    /// Manager's role for the vault (must hold StrategyManager)
+    #[account(
+        seeds = [b"user_role", strategy_state.vault.as_ref(), manager.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = manager_role.bump,
+        constraint = manager_role.has_role(ROLE_STRATEGY_MANAGER) @ StrategyError::Unauthorized
+    )]
+    pub manager_role: Account<'info, UserRole>,
+
+    /// Vault program
+    pub vault_program: Program<'info, VaultProgram>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Recall<'info> {
+    pub fn vault_transfer(&self) -> VaultTransfer<'_, 'info> {
+        VaultTransfer {
+            strategy_state: &self.strategy_state,
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            vault_program: &self.vault_program,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
+}
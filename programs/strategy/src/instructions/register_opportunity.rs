@@ -1,8 +1,11 @@
 use anchor_lang::prelude::*;
-use crate::state::{Opportunity, StrategyState};
+use user_management::{RoleAuthority, UserRole, PAUSE_REBALANCE, ROLE_STRATEGY_MANAGER};
+use crate::state::{Opportunity, ProtocolIndex, StrategyState, MAX_OPPORTUNITIES};
 use crate::scoring::ScoringEngine;
 use crate::errors::StrategyError;
 
+/// Register a protocol's opportunity at the next index of the strategy's
+/// registry, so clients can enumerate `0..num_opportunities` by PDA
 pub fn register_opportunity(
     ctx: Context<RegisterOpportunity>,
     protocol_id: [u8; 32],
@@ -16,10 +19,23 @@ pub fn register_opportunity(
         return Err(StrategyError::InvalidOpportunity.into());
     }
 
+    // Each protocol gets exactly one registry entry
+    if ctx.accounts.protocol_index.strategy != Pubkey::default() {
+        return Err(StrategyError::DuplicateProtocol.into());
+    }
+
+    let strategy_key = ctx.accounts.strategy_state.key();
+    let index = ctx.accounts.strategy_state.num_opportunities;
+    if index >= MAX_OPPORTUNITIES {
+        return Err(StrategyError::TooManyOpportunities.into());
+    }
+
     // Calculate risk-adjusted score
     let score = ScoringEngine::calculate_score(apy, volatility, il_risk, safety_score);
 
     let opportunity = &mut ctx.accounts.opportunity;
+    opportunity.strategy = strategy_key;
+    opportunity.index = index;
     opportunity.protocol_id = protocol_id;
     opportunity.apy = apy;
     opportunity.volatility = volatility;
@@ -30,38 +46,76 @@ pub fn register_opportunity(
     opportunity.last_updated = Clock::get()?.unix_timestamp;
     opportunity.bump = ctx.bumps.opportunity;
 
-    // Update strategy state
-    let strategy = &mut ctx.accounts.strategy_state;
-    if strategy.num_opportunities < 255 {
-        strategy.num_opportunities = strategy.num_opportunities.checked_add(1)
-            .ok_or(StrategyError::OverflowError)?;
-    }
+    let protocol_index = &mut ctx.accounts.protocol_index;
+    protocol_index.strategy = strategy_key;
+    protocol_index.index = index;
+    protocol_index.bump = ctx.bumps.protocol_index;
 
-    // Update best opportunity if this is better
-    if strategy.best_opportunity == 0 || score > ctx.accounts.best_opp_account.score {
-        strategy.best_opportunity = strategy.num_opportunities - 1;
+    // Update strategy state - the first opportunity is the best by default,
+    // later ones have to beat the current best's score
+    let opportunity_key = ctx.accounts.opportunity.key();
+    let strategy = &mut ctx.accounts.strategy_state;
+    strategy.num_opportunities = index + 1;
+    if strategy.best_opportunity == Pubkey::default() || score > strategy.best_score {
+        strategy.best_opportunity = opportunity_key;
+        strategy.best_score = score;
     }
 
     Ok(())
 }
 
 #[derive(Accounts)]
+#[instruction(protocol_id: [u8; 32])]
 pub struct RegisterOpportunity<'info> {
     /// Strategy state
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_state.vault.as_ref()],
+        bump = strategy_state.bump
+    )]
     pub strategy_state: Account<'info, StrategyState>,
 
-    /// New opportunity being registered
-    #[account(init, payer = admin, space = Opportunity::LEN)]
+    /// New opportunity, at the next index of the strategy's registry
+    #[account(
+        init,
+        payer = manager,
+        space = Opportunity::LEN,
+        seeds = [b"opportunity", strategy_state.key().as_ref(), &[strategy_state.num_opportunities]],
+        bump
+    )]
     pub opportunity: Account<'info, Opportunity>,
 
-    /// Reference to best opportunity (for comparison)
-    #[account(mut)]
-    pub best_opp_account: Account<'info, Opportunity>,
+    /// Registry entry for the protocol (must not exist yet)
+    #[account(
+        init_if_needed,
+        payer = manager,
+        space = ProtocolIndex::LEN,
+        seeds = [b"protocol", strategy_state.key().as_ref(), protocol_id.as_ref()],
+        bump
+    )]
+    pub protocol_index: Account<'info, ProtocolIndex>,
 
-    /// Admin signer
+    /// Strategy manager signer
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub manager: Signer<'info>,
+
+    /// Manager's role for the vault (must hold StrategyManager)
+    #[account(
+        seeds = [b"user_role", strategy_state.vault.as_ref(), manager.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = manager_role.bump,
+        constraint = manager_role.has_role(ROLE_STRATEGY_MANAGER) @ StrategyError::Unauthorized
+    )]
+    pub manager_role: Account<'info, UserRole>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", strategy_state.vault.as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_REBALANCE) @ StrategyError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
 
     /// System program
     pub system_program: Program<'info, System>,
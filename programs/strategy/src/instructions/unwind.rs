@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use vault::assets::AssetAccounts;
+use vault::program::Vault as VaultProgram;
+use vault::state::Vault;
+use crate::adapter::{adapter_balance, VaultTransfer};
+use crate::state::{DeployedPosition, Opportunity, StrategyState};
+use crate::errors::StrategyError;
+
+/// Recall what the vault's withdraw queue is short of from one opportunity's
+/// position (permissionless keeper call)
+///
+/// Queued withdraw requests are only paid from idle assets, so a keeper calls
+/// this - once per opportunity, until the shortfall is covered - before
+/// `process_withdraw_queue`. Nothing beyond the shortfall can be recalled,
+/// and it is never paused.
+pub fn unwind(ctx: Context<Unwind>) -> Result<()> {
+    let shortfall = ctx.accounts.asset_accounts().withdraw_shortfall()?;
+    if shortfall == 0 {
+        return Err(StrategyError::NoWithdrawShortfall.into());
+    }
+
+    let balance = adapter_balance(
+        &ctx.accounts.vault,
+        &ctx.accounts.adapter,
+        ctx.accounts.adapter_token.as_ref(),
+    )?;
+    let recalled = shortfall.min(balance);
+    if recalled == 0 {
+        return Err(StrategyError::InsufficientDeployed.into());
+    }
+
+    let position = &mut ctx.accounts.position;
+    let principal = position.record_recall(recalled)?;
+    ctx.accounts.vault_transfer().recall(
+        &ctx.accounts.opportunity.key(),
+        &ctx.accounts.adapter,
+        ctx.accounts.position.adapter_bump,
+        ctx.accounts.adapter_token.as_ref(),
+        recalled,
+        principal,
+    )?;
+
+    let strategy = &mut ctx.accounts.strategy_state;
+    strategy.deployed_value = strategy.deployed_value.saturating_sub(principal);
+
+    msg!("Unwound {} for the withdraw queue, {} short before", recalled, shortfall);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Unwind<'info> {
+    /// Strategy state
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_state.vault.as_ref()],
+        bump = strategy_state.bump
+    )]
+    pub strategy_state: Account<'info, StrategyState>,
+
+    /// Opportunity to recall from
+    #[account(
+        seeds = [b"opportunity", strategy_state.key().as_ref(), &[opportunity.index]],
+        bump = opportunity.bump
+    )]
+    pub opportunity: Account<'info, Opportunity>,
+
+    /// Position in the opportunity
+    #[account(
+        mut,
+        seeds = [b"position", opportunity.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, DeployedPosition>,
+
+    /// Opportunity's adapter
+    #[account(
+        mut,
+        seeds = [b"adapter", opportunity.key().as_ref()],
+        bump = position.adapter_bump
+    )]
+    pub adapter: SystemAccount<'info>,
+
+    /// Opportunity's adapter token account (token vaults only)
+    #[account(
+        mut,
+        seeds = [b"adapter_token", opportunity.key().as_ref()],
+        bump
+    )]
+    pub adapter_token: Option<Account<'info, TokenAccount>>,
+
+    /// Vault the strategy manages
+    #[account(mut, address = strategy_state.vault)]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault SOL account (read here to size the shortfall)
+    #[account(
+        mut,
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        seeds::program = vault::ID,
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only,
+    /// read here to size the shortfall)
+    #[account(
+        mut,
+        seeds = [b"vault_token", vault.key().as_ref()],
+        seeds::program = vault::ID,
+        bump = vault.vault_token_bump
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Keeper signer
+    pub keeper: Signer<'info>,
+
+    /// Vault program
+    pub vault_program: Program<'info, VaultProgram>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Unwind<'info> {
+    pub fn asset_accounts(&self) -> AssetAccounts<'_, 'info> {
+        AssetAccounts {
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
+
+    pub fn vault_transfer(&self) -> VaultTransfer<'_, 'info> {
+        VaultTransfer {
+            strategy_state: &self.strategy_state,
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            vault_program: &self.vault_program,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use user_management::{RoleAuthority, UserRole, PAUSE_REBALANCE, ROLE_STRATEGY_MANAGER};
+use crate::state::{Opportunity, StrategyState};
+use crate::scoring::ScoringEngine;
+use crate::errors::StrategyError;
+
+/// Recompute the weighted allocation's target table from current scores
+/// (StrategyManager role)
+///
+/// Pass every opportunity registered with the strategy as remaining
+/// accounts, in registry order. Active ones compete for the top `top_n`
+/// slots; see `ScoringEngine::target_weights` for how weights are set.
+pub fn update_targets<'info>(
+    ctx: Context<'_, '_, 'info, 'info, UpdateTargets<'info>>,
+) -> Result<()> {
+    let strategy_key = ctx.accounts.strategy_state.key();
+    let strategy = &mut ctx.accounts.strategy_state;
+    if !strategy.is_weighted() {
+        return Err(StrategyError::InvalidAllocation.into());
+    }
+    if ctx.remaining_accounts.len() != strategy.num_opportunities as usize {
+        return Err(StrategyError::InvalidOpportunity.into());
+    }
+
+    let mut candidates = Vec::with_capacity(ctx.remaining_accounts.len());
+    for (account, index) in ctx.remaining_accounts.iter().zip(0..) {
+        let opportunity = Opportunity::load_registered(account, &strategy_key, index)?;
+        if opportunity.active {
+            candidates.push((opportunity.key(), opportunity.score));
+        }
+    }
+
+    strategy.targets = ScoringEngine::target_weights(&candidates, &strategy.allocation);
+    for target in strategy.targets.iter() {
+        msg!("Target {}: {}%", target.opportunity, target.weight);
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateTargets<'info> {
+    /// Strategy state
+    #[account(
+        mut,
+        seeds = [b"strategy", strategy_state.vault.as_ref()],
+        bump = strategy_state.bump
+    )]
+    pub strategy_state: Account<'info, StrategyState>,
+
+    /// Strategy manager signer
+    pub manager: Signer<'info>,
+
+    /// Manager's role for the vault (must hold StrategyManager)
+    #[account(
+        seeds = [b"user_role", strategy_state.vault.as_ref(), manager.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = manager_role.bump,
+        constraint = manager_role.has_role(ROLE_STRATEGY_MANAGER) @ StrategyError::Unauthorized
+    )]
+    pub manager_role: Account<'info, UserRole>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", strategy_state.vault.as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_REBALANCE) @ StrategyError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+}
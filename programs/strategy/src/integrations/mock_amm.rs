@@ -9,7 +9,7 @@ pub struct MockAMM {
     pub deposited: u64,
     /// Accumulated yield
     pub yield_earned: u64,
-    /// Fee rate (in basis points, e.g., 500 = 0.5%)
+    /// Fee rate (in basis points, e.g., 500 = 5%)
     pub fee_rate: u16,
     /// Simulated IL accumulation
     pub il_loss: u64,
@@ -113,6 +113,6 @@ mod tests {
         amm.deposit(100_000).unwrap();
         let fees = amm.accrue_fees();
         // 100_000 * 500 / 10000 = 5000
-        assert_eq!(fees, 500);
+        assert_eq!(fees, 5000);
     }
 }
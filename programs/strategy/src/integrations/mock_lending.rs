@@ -56,11 +56,11 @@ impl MockLending {
         let time_passed = current_timestamp.saturating_sub(self.deposit_timestamp) as u64;
         let seconds_per_year = 365 * 24 * 60 * 60;
 
-        // Interest = Principal * Rate * Time / (100 * Year)
+        // Interest = Principal * Rate * Time / (10000 * Year), rate in percent * 100
         let interest = (self.deposited as u128)
             .checked_mul(self.annual_rate as u128)
             .and_then(|x| x.checked_mul(time_passed as u128))
-            .and_then(|x| x.checked_div(100 * seconds_per_year as u128))
+            .and_then(|x| x.checked_div(10_000 * seconds_per_year as u128))
             .unwrap_or(0) as u64;
 
         interest
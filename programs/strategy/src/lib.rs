@@ -1,14 +1,14 @@
 use anchor_lang::prelude::*;
 
+mod adapter;
 mod errors;
 mod scoring;
 mod state;
 mod integrations;
-mod rbac_helper;
 mod instructions;
 
 use errors::StrategyError;
-use state::{StrategyState, Opportunity};
+use state::{AllocationConfig, StrategyState, Opportunity};
 use scoring::ScoringEngine;
 use instructions::*;
 
@@ -33,8 +33,8 @@ pub mod strategy {
         instructions::register_opportunity(ctx, protocol_id, apy, volatility, il_risk, safety_score)
     }
 
-    pub fn evaluate(
-        ctx: Context<Evaluate>,
+    pub fn evaluate<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Evaluate<'info>>,
         new_apy: u16,
         new_volatility: u8,
         new_il_risk: u8,
@@ -46,4 +46,29 @@ pub mod strategy {
     pub fn rebalance(ctx: Context<Rebalance>) -> Result<()> {
         instructions::rebalance(ctx)
     }
+
+    pub fn recall(ctx: Context<Recall>, amount: u64) -> Result<()> {
+        instructions::recall(ctx, amount)
+    }
+
+    pub fn configure_allocation(
+        ctx: Context<ConfigureAllocation>,
+        config: AllocationConfig,
+    ) -> Result<()> {
+        instructions::configure_allocation(ctx, config)
+    }
+
+    pub fn update_targets<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateTargets<'info>>,
+    ) -> Result<()> {
+        instructions::update_targets(ctx)
+    }
+
+    pub fn rebalance_weighted(ctx: Context<RebalanceWeighted>) -> Result<()> {
+        instructions::rebalance_weighted(ctx)
+    }
+
+    pub fn unwind(ctx: Context<Unwind>) -> Result<()> {
+        instructions::unwind(ctx)
+    }
 }
@@ -8,6 +8,9 @@
 /// - Reduces score for IL risk (20% weight)
 /// - Rewards safer protocols (10% weight bonus)
 
+use anchor_lang::prelude::Pubkey;
+use crate::state::{AllocationConfig, AllocationTarget};
+
 pub struct ScoringEngine;
 
 impl ScoringEngine {
@@ -46,6 +49,17 @@ impl ScoringEngine {
         score_diff > threshold
     }
 
+    /// Best opportunity after the current best is re-scored
+    ///
+    /// `candidates` are the other active opportunities. The current best
+    /// keeps its place unless one of them now scores strictly higher; ties go
+    /// to the earliest registered.
+    pub fn rescan_best(current: (Pubkey, u16), candidates: &[(Pubkey, u16)]) -> (Pubkey, u16) {
+        candidates.iter().fold(current, |best, candidate| {
+            if candidate.1 > best.1 { *candidate } else { best }
+        })
+    }
+
     /// Calculate allocation based on score
     /// Returns percentage allocation (0-100)
     pub fn calculate_allocation(
@@ -59,6 +73,65 @@ impl ScoringEngine {
         let allocation = ((score as u32 * 100) / total_score as u32) as u8;
         std::cmp::min(100, allocation)
     }
+
+    /// Target weights for a weighted allocation
+    ///
+    /// Takes the `top_n` highest-scoring candidates (with a non-zero score) and
+    /// weights them in proportion to score. The lowest-scoring ones are dropped
+    /// while their weight is under `min_weight`. Any weight above `max_weight`
+    /// is capped and the excess shared among the rest. Weights round down, so
+    /// they sum to at most 100; whatever is left stays idle in the vault.
+    pub fn target_weights(
+        candidates: &[(Pubkey, u16)],
+        config: &AllocationConfig,
+    ) -> Vec<AllocationTarget> {
+        // Stable sort, so ties keep registry order
+        let mut top: Vec<(Pubkey, u16)> = candidates
+            .iter()
+            .copied()
+            .filter(|(_, score)| *score > 0)
+            .collect();
+        top.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        top.truncate(config.top_n as usize);
+
+        // Drop the weakest while it is under the minimum; each drop raises
+        // the weight of the rest
+        while let Some((_, score)) = top.last() {
+            let total: u16 = top.iter().map(|(_, score)| score).sum();
+            if Self::calculate_allocation(*score, total) >= config.min_weight {
+                break;
+            }
+            top.pop();
+        }
+
+        // Cap the strongest at the maximum, one at a time, until the
+        // strongest uncapped one fits
+        let max_weight = config.max_weight as u16;
+        let share = |score: u16, total: u16, remaining: u16| {
+            Self::calculate_allocation(score, total) as u16 * remaining / 100
+        };
+        let mut capped = 0;
+        let (total, remaining) = loop {
+            let total: u16 = top[capped..].iter().map(|(_, score)| score).sum();
+            let remaining = 100 - capped as u16 * max_weight;
+            match top.get(capped) {
+                Some((_, score)) if share(*score, total, remaining) > max_weight => capped += 1,
+                _ => break (total, remaining),
+            }
+        };
+
+        top.iter()
+            .enumerate()
+            .map(|(rank, (opportunity, score))| AllocationTarget {
+                opportunity: *opportunity,
+                weight: if rank < capped {
+                    config.max_weight
+                } else {
+                    share(*score, total, remaining) as u8
+                },
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -87,4 +160,77 @@ mod tests {
         assert!(ScoringEngine::should_rebalance(30, 50, 10));
         assert!(!ScoringEngine::should_rebalance(45, 50, 10));
     }
+
+    #[test]
+    fn test_rescan_best_after_score_drop() {
+        let current = Pubkey::new_unique();
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        // The highest of the others takes over once the best drops below it
+        let best = ScoringEngine::rescan_best((current, 20), &[(a, 30), (b, 45), (c, 10)]);
+        assert_eq!(best, (b, 45));
+        // Ties keep the earliest registered candidate
+        assert_eq!(ScoringEngine::rescan_best((current, 20), &[(a, 45), (b, 45)]), (a, 45));
+        // A drop that still leaves it on top, or no other active opportunity,
+        // keeps the current best
+        assert_eq!(ScoringEngine::rescan_best((current, 50), &[(a, 45), (b, 50)]), (current, 50));
+        assert_eq!(ScoringEngine::rescan_best((current, 0), &[]), (current, 0));
+    }
+
+    /// Target weights, highest score first
+    fn weights(scores: &[u16], config: AllocationConfig) -> Vec<u8> {
+        let candidates: Vec<(Pubkey, u16)> = scores
+            .iter()
+            .map(|score| (Pubkey::new_unique(), *score))
+            .collect();
+        ScoringEngine::target_weights(&candidates, &config)
+            .iter()
+            .map(|target| target.weight)
+            .collect()
+    }
+
+    fn config(top_n: u8, min_weight: u8, max_weight: u8) -> AllocationConfig {
+        AllocationConfig { top_n, min_weight, max_weight, drift_threshold: 5 }
+    }
+
+    #[test]
+    fn test_target_weights_follow_score() {
+        // Top 3 of 4 by score, in proportion; zero scores never get capital
+        assert_eq!(weights(&[10, 50, 0, 30, 20], config(3, 0, 100)), vec![50, 30, 20]);
+        assert_eq!(weights(&[40, 40], config(4, 0, 100)), vec![50, 50]);
+        assert!(weights(&[0, 0], config(2, 0, 100)).is_empty());
+    }
+
+    #[test]
+    fn test_target_weights_drop_below_min() {
+        // 60/30/10 -> the 10% slot is under the minimum and its share goes
+        // to the others (66/33)
+        assert_eq!(weights(&[60, 30, 10], config(3, 15, 100)), vec![66, 33]);
+        // Dropping can leave a single opportunity with all the capital
+        assert_eq!(weights(&[50, 50], config(2, 60, 100)), vec![100]);
+    }
+
+    #[test]
+    fn test_target_weights_cap_and_redistribute() {
+        // 80/10/10 capped at 50: the excess is shared by the other two
+        assert_eq!(weights(&[80, 10, 10], config(3, 0, 50)), vec![50, 25, 25]);
+        // Capping can cascade: 70/20/10 at 40 -> 40, then 20/10 share 60
+        // (40/20) and the 40 fits
+        assert_eq!(weights(&[70, 20, 10], config(3, 0, 40)), vec![40, 39, 19]);
+        // Every slot capped leaves the rest idle
+        assert_eq!(weights(&[50, 50], config(2, 0, 30)), vec![30, 30]);
+        assert!(weights(&[90, 5, 5], config(3, 0, 40)).iter().all(|w| *w <= 40));
+    }
+
+    #[test]
+    fn test_allocation_config_validation() {
+        assert!(AllocationConfig::default().validate().is_ok());
+        assert!(config(3, 10, 50).validate().is_ok());
+        assert!(config(9, 0, 50).validate().is_err());
+        assert!(config(3, 60, 50).validate().is_err());
+        assert!(config(3, 0, 0).validate().is_err());
+        assert!(config(3, 0, 101).validate().is_err());
+        let no_drift = AllocationConfig { drift_threshold: 0, ..config(3, 10, 50) };
+        assert!(no_drift.validate().is_err());
+    }
 }
@@ -1,31 +1,128 @@
 use anchor_lang::prelude::*;
+use crate::errors::StrategyError;
+
+/// Most opportunities a weighted allocation spreads capital across
+pub const MAX_ALLOCATIONS: usize = 8;
+
+/// Smallest drift threshold (% of capital) of a weighted allocation, so
+/// `rebalance_weighted` never moves dust
+pub const MIN_DRIFT_THRESHOLD: u8 = 1;
+
+/// Minimum time between two rebalances of the strategy (winner-take-all) or
+/// of one position (weighted)
+pub const REBALANCE_COOLDOWN: i64 = 3600; // 1 hour
+
+/// Most opportunities a strategy can register, so that re-scoring the best
+/// one can still pass every other opportunity in a single transaction
+pub const MAX_OPPORTUNITIES: u8 = 16;
 
 /// Strategy configuration and state
 #[account]
 pub struct StrategyState {
     /// Associated vault
     pub vault: Pubkey,
-    /// Current best opportunity
-    pub best_opportunity: u8,
-    /// Number of opportunities evaluated
+    /// Highest-scoring active opportunity (default until one is registered)
+    pub best_opportunity: Pubkey,
+    /// Score of `best_opportunity`
+    pub best_score: u16,
+    /// Number of opportunities registered (next registry index)
     pub num_opportunities: u8,
     /// Threshold for rebalancing (score difference)
     pub rebalance_threshold: u16,
     /// Last rebalance timestamp
     pub last_rebalance: i64,
+    /// Opportunity holding the deployed assets (default while nothing is deployed)
+    pub current_opportunity: Pubkey,
     /// Total value currently deployed in strategies
     pub deployed_value: u64,
     /// Bump seed
     pub bump: u8,
+    /// Weighted allocation settings (`top_n == 0` keeps winner-take-all rebalancing)
+    pub allocation: AllocationConfig,
+    /// Target weights set by `update_targets`; opportunities not listed target 0
+    pub targets: Vec<AllocationTarget>,
 }
 
 impl StrategyState {
-    pub const LEN: usize = 8 + 32 + 1 + 1 + 2 + 8 + 8 + 1;
+    pub const LEN: usize = 8 + 32 + 32 + 2 + 1 + 2 + 8 + 32 + 8 + 1
+        + AllocationConfig::LEN
+        + 4 + MAX_ALLOCATIONS * AllocationTarget::LEN;
+
+    /// Whether capital is spread across weighted targets rather than moved
+    /// to the single best opportunity
+    pub fn is_weighted(&self) -> bool {
+        self.allocation.is_weighted()
+    }
+
+    /// Target weight (percent) of `opportunity`
+    pub fn target_weight(&self, opportunity: &Pubkey) -> u8 {
+        self.targets
+            .iter()
+            .find(|target| target.opportunity == *opportunity)
+            .map_or(0, |target| target.weight)
+    }
+}
+
+/// Weighted allocation settings, all in percent
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocationConfig {
+    /// Number of top-scoring active opportunities to spread capital across
+    /// (0 = winner-take-all)
+    pub top_n: u8,
+    /// Smallest weight worth holding - lower-weighted opportunities are dropped
+    pub min_weight: u8,
+    /// Largest weight of any one opportunity - the excess goes to the others
+    pub max_weight: u8,
+    /// How far a position may drift from its target, as a share of the
+    /// strategy's capital, before `rebalance_weighted` moves it
+    pub drift_threshold: u8,
+}
+
+impl AllocationConfig {
+    pub const LEN: usize = 1 + 1 + 1 + 1;
+
+    /// Whether these settings spread capital across weighted targets
+    pub fn is_weighted(&self) -> bool {
+        self.top_n > 0
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !self.is_weighted() {
+            return Ok(());
+        }
+        if self.top_n as usize > MAX_ALLOCATIONS
+            || self.max_weight == 0
+            || self.max_weight > 100
+            || self.min_weight > self.max_weight
+            || !(MIN_DRIFT_THRESHOLD..=100).contains(&self.drift_threshold)
+        {
+            return Err(StrategyError::InvalidAllocation.into());
+        }
+        Ok(())
+    }
 }
 
-/// Yield opportunity from a protocol
+/// Target share of the strategy's capital for one opportunity
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocationTarget {
+    /// Opportunity account
+    pub opportunity: Pubkey,
+    /// Weight in percent
+    pub weight: u8,
+}
+
+impl AllocationTarget {
+    pub const LEN: usize = 32 + 1;
+}
+
+/// Yield opportunity from a protocol, at PDA
+/// `[b"opportunity", strategy_state, &[index]]`
 #[account]
 pub struct Opportunity {
+    /// Strategy this opportunity is registered with
+    pub strategy: Pubkey,
+    /// Position in the strategy's registry (0..num_opportunities)
+    pub index: u8,
     /// Protocol identifier (e.g., "Meteora_SOL_USDC", "Kamino_SOL")
     pub protocol_id: [u8; 32],
     /// Current APY (as percentage * 100, e.g., 1050 = 10.50%)
@@ -47,24 +144,138 @@ pub struct Opportunity {
 }
 
 impl Opportunity {
-    pub const LEN: usize = 8 + 32 + 2 + 1 + 1 + 1 + 2 + 1 + 8 + 1;
+    pub const LEN: usize = 8 + 32 + 1 + 32 + 2 + 1 + 1 + 1 + 2 + 1 + 8 + 1;
+
+    /// Load the opportunity registered with `strategy` at `index`, checking
+    /// that `account` is its PDA
+    pub fn load_registered<'info>(
+        account: &'info AccountInfo<'info>,
+        strategy: &Pubkey,
+        index: u8,
+    ) -> Result<Account<'info, Opportunity>> {
+        let opportunity: Account<'info, Opportunity> = Account::try_from(account)?;
+        let address = Pubkey::create_program_address(
+            &[b"opportunity", strategy.as_ref(), &[index], &[opportunity.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| StrategyError::InvalidOpportunity)?;
+        if account.key() != address {
+            return Err(StrategyError::InvalidOpportunity.into());
+        }
+        Ok(opportunity)
+    }
 }
 
-/// Represents a deployed position in a protocol
+/// Registry entry for a protocol, at PDA
+/// `[b"protocol", strategy_state, protocol_id]` - one per protocol, so the
+/// same protocol cannot be registered twice
+#[account]
+pub struct ProtocolIndex {
+    /// Strategy the protocol is registered with
+    pub strategy: Pubkey,
+    /// Registry index of the protocol's opportunity
+    pub index: u8,
+    /// Bump seed
+    pub bump: u8,
+}
+
+impl ProtocolIndex {
+    pub const LEN: usize = 8 + 32 + 1 + 1;
+}
+
+/// Assets deployed to an opportunity, at PDA `[b"position", opportunity]`
+///
+/// The assets themselves sit with the opportunity's adapter, the
+/// `[b"adapter", opportunity]` PDA (see `adapter.rs`).
 #[account]
 pub struct DeployedPosition {
     /// Which opportunity this is deployed to
     pub opportunity: Pubkey,
-    /// Amount deployed (in lamports)
+    /// Principal deployed, in the vault's underlying asset
     pub amount: u64,
-    /// Timestamp of deployment
+    /// Last time the strategy deployed to this position, or moved it toward
+    /// its weighted target
     pub deployment_timestamp: i64,
     /// Accumulated yield from this position
     pub yield_earned: u64,
     /// Bump seed
     pub bump: u8,
+    /// Bump seed of the opportunity's adapter PDA
+    pub adapter_bump: u8,
 }
 
 impl DeployedPosition {
-    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1;
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1 + 1;
+
+    /// Book `recalled` coming back from the adapter: principal first, the
+    /// rest as yield. Returns the principal returned.
+    pub fn record_recall(&mut self, recalled: u64) -> Result<u64> {
+        let principal = recalled.min(self.amount);
+        self.amount -= principal;
+        self.yield_earned = self.yield_earned.checked_add(recalled - principal)
+            .ok_or(StrategyError::OverflowError)?;
+        Ok(principal)
+    }
+
+    /// Book the adapter's whole `balance` coming back as the position is
+    /// left. Returns the principal closed out - all of `amount`, so a balance
+    /// below it is the loss the vault realizes.
+    pub fn record_exit(&mut self, balance: u64) -> Result<u64> {
+        let principal = self.amount;
+        self.record_recall(balance)?;
+        self.amount = 0;
+        Ok(principal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deployed(amount: u64) -> DeployedPosition {
+        DeployedPosition {
+            opportunity: Pubkey::default(),
+            amount,
+            deployment_timestamp: 0,
+            yield_earned: 0,
+            bump: 0,
+            adapter_bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_full_recall_books_the_gain_as_yield() {
+        let mut position = deployed(1_000);
+        assert_eq!(position.record_recall(1_050).unwrap(), 1_000);
+        assert_eq!(position.amount, 0);
+        assert_eq!(position.yield_earned, 50);
+    }
+
+    #[test]
+    fn test_partial_recall_returns_principal_first() {
+        let mut position = deployed(1_000);
+        assert_eq!(position.record_recall(400).unwrap(), 400);
+        assert_eq!(position.amount, 600);
+        assert_eq!(position.yield_earned, 0);
+
+        // Only what comes back beyond the remaining principal is yield
+        assert_eq!(position.record_recall(700).unwrap(), 600);
+        assert_eq!(position.amount, 0);
+        assert_eq!(position.yield_earned, 100);
+    }
+
+    #[test]
+    fn test_exit_below_principal_closes_out_the_loss() {
+        // The adapter lost 300: all 1_000 of principal leaves the books, so
+        // the vault realizes the 300 it never got back
+        let mut position = deployed(1_000);
+        assert_eq!(position.record_exit(700).unwrap(), 1_000);
+        assert_eq!(position.amount, 0);
+        assert_eq!(position.yield_earned, 0);
+
+        // Exiting with a gain closes out the principal and books the rest
+        let mut position = deployed(1_000);
+        assert_eq!(position.record_exit(1_200).unwrap(), 1_000);
+        assert_eq!(position.yield_earned, 200);
+    }
 }
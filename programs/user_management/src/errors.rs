@@ -25,4 +25,10 @@ pub enum RbacError {
 
     #[msg("Invalid role combination")]
     InvalidRoleCombination,
+
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+
+    #[msg("Account is not a vault of the vault program")]
+    InvalidVault,
 }
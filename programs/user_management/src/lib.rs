@@ -12,12 +12,16 @@ declare_id!("BE2bDcazEUS5YpSW2JgJ2CAeTErp8LrpkZWAuW8WXa4h");
 pub mod user_management {
     use super::*;
 
-    /// Initialize role authority for a vault
+    /// Initialize role authority for a vault (the vault's admin only)
     pub fn initialize_role_authority(ctx: Context<InitializeRoleAuthority>) -> Result<()> {
         if ctx.accounts.role_authority.initialized {
             return Err(RbacError::AlreadyInitialized.into());
         }
 
+        if vault_admin(&ctx.accounts.vault)? != ctx.accounts.admin.key() {
+            return Err(RbacError::Unauthorized.into());
+        }
+
         let vault_key = ctx.accounts.vault.key();
         let admin_key = ctx.accounts.admin.key();
 
@@ -25,7 +29,7 @@ pub mod user_management {
         role_authority.vault = vault_key;
         role_authority.super_admin = admin_key;
         role_authority.initialized = true;
-        role_authority.emergency_pause = false;
+        role_authority.paused = 0;
         role_authority.bump = ctx.bumps.role_authority;
 
         let user_role = &mut ctx.accounts.user_role;
@@ -45,7 +49,7 @@ pub mod user_management {
         target_user: Pubkey,
         role: u8,
     ) -> Result<()> {
-        if ctx.accounts.role_authority.emergency_pause {
+        if ctx.accounts.role_authority.is_paused(PAUSE_ROLE_CHANGES) {
             return Err(RbacError::EmergencyPaused.into());
         }
 
@@ -53,11 +57,7 @@ pub mod user_management {
             return Err(RbacError::NotInitialized.into());
         }
 
-        if !ctx.accounts.admin_role.has_role(ROLE_ADMIN) {
-            return Err(RbacError::Unauthorized.into());
-        }
-
-        if role == 0 || role > 15 {
+        if role == 0 || role & !ALL_ROLES != 0 {
             return Err(RbacError::InvalidRole.into());
         }
 
@@ -84,7 +84,7 @@ pub mod user_management {
         target_user: Pubkey,
         role: u8,
     ) -> Result<()> {
-        if ctx.accounts.role_authority.emergency_pause {
+        if ctx.accounts.role_authority.is_paused(PAUSE_ROLE_CHANGES) {
             return Err(RbacError::EmergencyPaused.into());
         }
 
@@ -92,10 +92,6 @@ pub mod user_management {
             return Err(RbacError::NotInitialized.into());
         }
 
-        if !ctx.accounts.admin_role.has_role(ROLE_ADMIN) {
-            return Err(RbacError::Unauthorized.into());
-        }
-
         let role_authority = &ctx.accounts.role_authority;
         let target_role = &mut ctx.accounts.target_user_role;
 
@@ -105,10 +101,11 @@ pub mod user_management {
 
         target_role.remove_role(role);
 
-        if (role & ROLE_ADMIN) != 0 {
-            if role_authority.super_admin == target_user && !target_role.has_role(ROLE_ADMIN) {
-                return Err(RbacError::MustHaveAdmin.into());
-            }
+        if (role & ROLE_ADMIN) != 0
+            && role_authority.super_admin == target_user
+            && !target_role.has_role(ROLE_ADMIN)
+        {
+            return Err(RbacError::MustHaveAdmin.into());
         }
 
         target_role.assigned_at = Clock::get()?.unix_timestamp;
@@ -118,8 +115,10 @@ pub mod user_management {
     }
 
     /// Check if a user has a specific role (view function)
+    ///
+    /// Reports no roles while every operation is paused.
     pub fn has_role(ctx: Context<HasRole>, role: u8) -> Result<bool> {
-        if ctx.accounts.role_authority.emergency_pause {
+        if ctx.accounts.role_authority.paused == PAUSE_ALL {
             return Ok(false);
         }
 
@@ -130,28 +129,69 @@ pub mod user_management {
         Ok(ctx.accounts.user_role.has_role(role))
     }
 
-    /// Toggle emergency pause (super admin only)
-    pub fn emergency_pause(ctx: Context<EmergencyPause>) -> Result<()> {
-        let role_authority = &mut ctx.accounts.role_authority;
-        let super_admin = role_authority.super_admin;
+    /// Pause the given operations (Admin or Guardian role)
+    pub fn pause(ctx: Context<SetPause>, flags: u8) -> Result<()> {
+        if flags == 0 || flags & !PAUSE_ALL != 0 {
+            return Err(RbacError::InvalidPauseFlags.into());
+        }
+
+        if !ctx.accounts.role_authority.initialized {
+            return Err(RbacError::NotInitialized.into());
+        }
 
-        if ctx.accounts.caller.key() != super_admin {
+        if !ctx.accounts.caller_role.has_role(ROLE_ADMIN | ROLE_GUARDIAN) {
             return Err(RbacError::Unauthorized.into());
         }
 
-        if !role_authority.initialized {
+        let role_authority = &mut ctx.accounts.role_authority;
+        role_authority.paused |= flags;
+        msg!("Paused operations now {:#08b}", role_authority.paused);
+
+        Ok(())
+    }
+
+    /// Resume the given operations (Admin role only - guardians cannot unpause)
+    pub fn unpause(ctx: Context<SetPause>, flags: u8) -> Result<()> {
+        if flags == 0 || flags & !PAUSE_ALL != 0 {
+            return Err(RbacError::InvalidPauseFlags.into());
+        }
+
+        if !ctx.accounts.role_authority.initialized {
             return Err(RbacError::NotInitialized.into());
         }
 
-        role_authority.emergency_pause = !role_authority.emergency_pause;
+        if !ctx.accounts.caller_role.has_role(ROLE_ADMIN) {
+            return Err(RbacError::Unauthorized.into());
+        }
+
+        let role_authority = &mut ctx.accounts.role_authority;
+        role_authority.paused &= !flags;
+        msg!("Paused operations now {:#08b}", role_authority.paused);
 
         Ok(())
     }
 }
 
+/// Admin recorded in a vault account, which must belong to the vault program
+///
+/// The vault program depends on this one, so its `Vault` type is read by
+/// layout here rather than imported.
+fn vault_admin(vault: &AccountInfo) -> Result<Pubkey> {
+    let data = vault.try_borrow_data()?;
+    let discriminator = anchor_lang::solana_program::hash::hash(b"account:Vault").to_bytes();
+    if data.len() < VAULT_ADMIN_OFFSET + 32 || data[..8] != discriminator[..8] {
+        return Err(RbacError::InvalidVault.into());
+    }
+    Ok(Pubkey::new_from_array(
+        data[VAULT_ADMIN_OFFSET..VAULT_ADMIN_OFFSET + 32].try_into().unwrap(),
+    ))
+}
+
 #[derive(Accounts)]
 pub struct InitializeRoleAuthority<'info> {
-    /// CHECK: Vault address is only used to derive PDAs, no validation needed
+    /// CHECK: Owner checked here, admin checked against the signer in the
+    /// handler
+    #[account(owner = VAULT_PROGRAM_ID @ RbacError::InvalidVault)]
     pub vault: UncheckedAccount<'info>,
 
     #[account(
@@ -184,6 +224,11 @@ pub struct AssignRole<'info> {
     #[account(mut)]
     pub role_authority: Account<'info, RoleAuthority>,
 
+    #[account(
+        seeds = [b"user_role", role_authority.vault.as_ref(), admin.key().as_ref()],
+        bump = admin_role.bump,
+        constraint = admin_role.has_role(ROLE_ADMIN) @ RbacError::Unauthorized
+    )]
     pub admin_role: Account<'info, UserRole>,
 
     #[account(
@@ -207,6 +252,11 @@ pub struct RevokeRole<'info> {
     #[account(mut)]
     pub role_authority: Account<'info, RoleAuthority>,
 
+    #[account(
+        seeds = [b"user_role", role_authority.vault.as_ref(), admin.key().as_ref()],
+        bump = admin_role.bump,
+        constraint = admin_role.has_role(ROLE_ADMIN) @ RbacError::Unauthorized
+    )]
     pub admin_role: Account<'info, UserRole>,
 
     #[account(
@@ -230,9 +280,15 @@ pub struct HasRole<'info> {
 }
 
 #[derive(Accounts)]
-pub struct EmergencyPause<'info> {
+pub struct SetPause<'info> {
     #[account(mut)]
     pub role_authority: Account<'info, RoleAuthority>,
 
+    #[account(
+        seeds = [b"user_role", role_authority.vault.as_ref(), caller.key().as_ref()],
+        bump = caller_role.bump
+    )]
+    pub caller_role: Account<'info, UserRole>,
+
     pub caller: Signer<'info>,
 }
@@ -5,6 +5,35 @@ pub const ROLE_REGULAR_USER: u8 = 1 << 0; // bit 0
 pub const ROLE_ADMIN: u8 = 1 << 1;       // bit 1
 pub const ROLE_STRATEGY_MANAGER: u8 = 1 << 2; // bit 2
 pub const ROLE_TREASURY: u8 = 1 << 3;   // bit 3
+pub const ROLE_GUARDIAN: u8 = 1 << 4;   // bit 4
+/// Every defined role bit
+pub const ALL_ROLES: u8 = ROLE_REGULAR_USER
+    | ROLE_ADMIN
+    | ROLE_STRATEGY_MANAGER
+    | ROLE_TREASURY
+    | ROLE_GUARDIAN;
+
+/// Pause bitfield constants - each bit pauses one kind of operation
+pub const PAUSE_DEPOSITS: u8 = 1 << 0;     // bit 0
+pub const PAUSE_WITHDRAWALS: u8 = 1 << 1;  // bit 1
+pub const PAUSE_HARVEST: u8 = 1 << 2;      // bit 2
+pub const PAUSE_REBALANCE: u8 = 1 << 3;    // bit 3
+pub const PAUSE_ROLE_CHANGES: u8 = 1 << 4; // bit 4
+pub const PAUSE_ADMIN: u8 = 1 << 5;        // bit 5
+/// Every defined pause bit
+pub const PAUSE_ALL: u8 = PAUSE_DEPOSITS
+    | PAUSE_WITHDRAWALS
+    | PAUSE_HARVEST
+    | PAUSE_REBALANCE
+    | PAUSE_ROLE_CHANGES
+    | PAUSE_ADMIN;
+
+/// Vault program, whose vaults role authorities are created for
+pub const VAULT_PROGRAM_ID: Pubkey = pubkey!("76MQ83iPkH4ERPWq8cDKwo7KaQrSpkdwF6qdTbHi7Q7j");
+
+/// Offset of `Vault.admin` in a vault account (discriminator, total_assets,
+/// total_shares)
+pub const VAULT_ADMIN_OFFSET: usize = 8 + 8 + 8;
 
 /// User's role assignment for a specific vault
 #[account]
@@ -18,6 +47,7 @@ pub struct UserRole {
     /// bit 1: Admin (can initialize, harvest, manage roles)
     /// bit 2: StrategyManager (can register opportunities, rebalance)
     /// bit 3: Treasury (can withdraw fees)
+    /// bit 4: Guardian (can pause operations, but not unpause them)
     pub roles: u8,
     /// Timestamp when role was assigned
     pub assigned_at: i64,
@@ -55,12 +85,17 @@ pub struct RoleAuthority {
     pub super_admin: Pubkey,
     /// Whether role authority has been initialized
     pub initialized: bool,
-    /// Emergency pause flag
-    pub emergency_pause: bool,
+    /// Paused operations (`PAUSE_*` bitfield)
+    pub paused: u8,
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl RoleAuthority {
     pub const LEN: usize = 8 + 32 + 32 + 1 + 1 + 1;
+
+    /// Check if any of the given operations is paused
+    pub fn is_paused(&self, flags: u8) -> bool {
+        (self.paused & flags) != 0
+    }
 }
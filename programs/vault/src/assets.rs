@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, TransferChecked};
+use crate::errors::VaultError;
+use crate::state::Vault;
+
+/// Accounts needed to move a vault's underlying asset in or out.
+///
+/// SOL vaults move lamports through the vault SOL PDA. Token vaults move
+/// tokens through the `[b"vault_token", vault]` account with
+/// `transfer_checked`, so `asset_mint` and `vault_token_account` are only
+/// required when the vault is denominated in an SPL token.
+pub struct AssetAccounts<'a, 'info> {
+    pub vault: &'a Account<'info, Vault>,
+    pub vault_account: &'a SystemAccount<'info>,
+    pub asset_mint: Option<&'a Account<'info, Mint>>,
+    pub vault_token_account: Option<&'a Account<'info, TokenAccount>>,
+    pub system_program: &'a Program<'info, System>,
+    pub token_program: &'a Program<'info, Token>,
+}
+
+impl<'a, 'info> AssetAccounts<'a, 'info> {
+    /// Pull `amount` of the underlying asset from `owner` into the vault
+    pub fn transfer_in(
+        &self,
+        owner: &Signer<'info>,
+        owner_token_account: Option<&Account<'info, TokenAccount>>,
+        amount: u64,
+    ) -> Result<()> {
+        if self.vault.is_native() {
+            return system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: owner.to_account_info(),
+                        to: self.vault_account.to_account_info(),
+                    },
+                ),
+                amount,
+            );
+        }
+
+        let (asset_mint, vault_token_account) = self.token_accounts()?;
+        let from = owner_token_account.ok_or(VaultError::MissingTokenAccount)?;
+        token::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: from.to_account_info(),
+                    mint: asset_mint.to_account_info(),
+                    to: vault_token_account.to_account_info(),
+                    authority: owner.to_account_info(),
+                },
+            ),
+            amount,
+            self.vault.asset_decimals,
+        )
+    }
+
+    /// Pay `amount` of the underlying asset out of the vault, signed by the vault SOL PDA
+    pub fn transfer_out(
+        &self,
+        recipient: &AccountInfo<'info>,
+        recipient_token_account: Option<&Account<'info, TokenAccount>>,
+        amount: u64,
+    ) -> Result<()> {
+        let vault_key = self.vault.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault_sol",
+            vault_key.as_ref(),
+            &[self.vault.vault_account_bump],
+        ]];
+
+        if self.vault.is_native() {
+            return system_program::transfer(
+                CpiContext::new_with_signer(
+                    self.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: self.vault_account.to_account_info(),
+                        to: recipient.clone(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+            );
+        }
+
+        let (asset_mint, vault_token_account) = self.token_accounts()?;
+        let to = recipient_token_account.ok_or(VaultError::MissingTokenAccount)?;
+        token::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: vault_token_account.to_account_info(),
+                    mint: asset_mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.vault_account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            self.vault.asset_decimals,
+        )
+    }
+
+    /// Underlying asset actually held by the vault, excluding the SOL PDA's
+    /// rent reserve
+    pub fn custody_balance(&self) -> Result<u64> {
+        if self.vault.is_native() {
+            let rent_reserve = Rent::get()?.minimum_balance(0);
+            return Ok(self.vault_account.lamports().saturating_sub(rent_reserve));
+        }
+        Ok(self.token_accounts()?.1.amount)
+    }
+
+    /// Assets in custody that are free to pay out: excludes accumulated fees
+    /// and assets set aside for queued withdrawals
+    pub fn available_assets(&self) -> Result<u64> {
+        Ok(self
+            .custody_balance()?
+            .saturating_sub(self.vault.accumulated_fees)
+            .saturating_sub(self.vault.claimable_withdraw_assets))
+    }
+
+    /// Available assets left once the withdraw queue is covered: all that
+    /// instant withdrawals and strategy deployments may use, so neither can
+    /// jump ahead of a queued request
+    pub fn idle_assets(&self) -> Result<u64> {
+        Ok(self.available_assets()?.saturating_sub(self.vault.queued_withdraw_assets))
+    }
+
+    /// Custody that accumulated fees may be paid from: excludes assets owed
+    /// to processed withdraw requests and those reserved for queued ones
+    pub fn fee_liquidity(&self) -> Result<u64> {
+        Ok(self
+            .custody_balance()?
+            .saturating_sub(self.vault.claimable_withdraw_assets)
+            .saturating_sub(self.vault.queued_withdraw_assets))
+    }
+
+    /// Assets the withdraw queue needs beyond what is available
+    pub fn withdraw_shortfall(&self) -> Result<u64> {
+        Ok(self.vault.queued_withdraw_assets.saturating_sub(self.available_assets()?))
+    }
+
+    fn token_accounts(&self) -> Result<(&'a Account<'info, Mint>, &'a Account<'info, TokenAccount>)> {
+        let asset_mint = self.asset_mint.ok_or(VaultError::MissingTokenAccount)?;
+        let vault_token_account = self
+            .vault_token_account
+            .ok_or(VaultError::MissingTokenAccount)?;
+        Ok((asset_mint, vault_token_account))
+    }
+}
@@ -2,7 +2,7 @@ use anchor_lang::prelude::*;
 
 #[error_code]
 pub enum VaultError {
-    #[msg("Unauthorized - only admin can perform this action")]
+    #[msg("Unauthorized - caller lacks the role this action requires")]
     Unauthorized,
 
     #[msg("Invalid amount - must be greater than zero")]
@@ -35,9 +35,66 @@ pub enum VaultError {
     #[msg("RBAC: Invalid role value")]
     InvalidRole,
 
-    #[msg("RBAC: System is under emergency pause")]
+    #[msg("RBAC: Operation is paused")]
     EmergencyPaused,
 
     #[msg("RBAC: Role authority not found")]
     RoleAuthorityNotFound,
+
+    #[msg("Token accounts are required for SPL-token vaults")]
+    MissingTokenAccount,
+
+    #[msg("Slippage exceeded - result is outside the caller's bound")]
+    SlippageExceeded,
+
+    #[msg("Invalid exit policy - lock duration or penalty out of range")]
+    InvalidExitPolicy,
+
+    #[msg("Not enough idle assets in the vault - use request_withdraw")]
+    InsufficientLiquidity,
+
+    #[msg("Withdraw request is not next in the queue")]
+    InvalidWithdrawRequest,
+
+    #[msg("Withdraw request has not been processed yet")]
+    WithdrawNotReady,
+
+    #[msg("Invalid fee config - a fee is above its cap")]
+    InvalidFeeConfig,
+
+    #[msg("No fee change is ready to apply")]
+    FeeUpdateNotReady,
+
+    #[msg("Invalid profit unlock period")]
+    InvalidUnlockPeriod,
+
+    #[msg("Deposit is below the vault's minimum")]
+    DepositTooSmall,
+
+    #[msg("Deposit would exceed the vault's TVL cap")]
+    TvlCapExceeded,
+
+    #[msg("Deposit would exceed the per-user cap")]
+    UserCapExceeded,
+
+    #[msg("Invalid deposit limits - minimum deposit above a cap")]
+    InvalidDepositLimits,
+
+    #[msg("Depositor lacks the RegularUser role this vault requires")]
+    NotAllowlisted,
+
+    #[msg("Invalid fee recipient")]
+    InvalidTreasury,
+
+    #[msg("Fee split must have distinct recipients with shares summing to 10,000 bps")]
+    InvalidFeeSplit,
+
+    #[msg("Fees are split between recipients - use distribute_fees")]
+    FeeSplitActive,
+
+    #[msg("Nothing to deploy - strategy allocation reached or no idle assets")]
+    StrategyCapacityReached,
+
+    #[msg("No exit policy change is ready to apply")]
+    ExitPolicyUpdateNotReady,
 }
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use user_management::{RoleAuthority, PAUSE_ADMIN};
+use crate::state::Vault;
+use crate::errors::VaultError;
+
+/// Activate a scheduled exit policy change once its timelock has passed
+/// (anyone can call)
+pub fn apply_exit_policy(ctx: Context<ApplyExitPolicy>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    if vault.pending_exit_policy_eta == 0 || current_time < vault.pending_exit_policy_eta {
+        return Err(VaultError::ExitPolicyUpdateNotReady.into());
+    }
+
+    vault.exit_policy = vault.pending_exit_policy;
+    vault.pending_exit_policy_eta = 0;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ApplyExitPolicy<'info> {
+    /// Vault account
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_ADMIN) @ VaultError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use user_management::{RoleAuthority, PAUSE_ADMIN};
+use crate::state::Vault;
+use crate::errors::VaultError;
+
+/// Activate a scheduled fee change once its timelock has passed (anyone can call)
+pub fn apply_fee_config(ctx: Context<ApplyFeeConfig>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    if vault.pending_fee_config_eta == 0 || current_time < vault.pending_fee_config_eta {
+        return Err(VaultError::FeeUpdateNotReady.into());
+    }
+
+    // Management fee up to now is charged at the old rate
+    vault.accrue(current_time)?;
+
+    vault.fee_config = vault.pending_fee_config;
+    vault.pending_fee_config_eta = 0;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ApplyFeeConfig<'info> {
+    /// Vault account
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_ADMIN) @ VaultError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::assets::AssetAccounts;
+use crate::state::{Vault, WithdrawRequest};
+use crate::errors::VaultError;
+
+/// Pay out a processed withdraw request and close it
+///
+/// Never paused - the assets were already set aside for the user when the
+/// request was processed.
+pub fn claim_withdraw(ctx: Context<ClaimWithdraw>) -> Result<()> {
+    let assets = ctx.accounts.withdraw_request.assets;
+
+    // Pay the assets set aside at processing, signed by the vault SOL PDA
+    ctx.accounts.asset_accounts().transfer_out(
+        &ctx.accounts.user.to_account_info(),
+        ctx.accounts.user_token_account.as_ref(),
+        assets,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.claimable_withdraw_assets = vault
+        .claimable_withdraw_assets
+        .checked_sub(assets)
+        .ok_or(VaultError::OverflowError)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimWithdraw<'info> {
+    /// Vault account
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    /// User's wallet (receives the assets and the request's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Processed withdraw request
+    #[account(
+        mut,
+        close = user,
+        has_one = vault,
+        has_one = user,
+        constraint = withdraw_request.fulfilled @ VaultError::WithdrawNotReady,
+        seeds = [b"withdraw_request", vault.key().as_ref(), &withdraw_request.id.to_le_bytes()],
+        bump = withdraw_request.bump
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    /// Vault SOL account (PDA that holds funds)
+    #[account(
+        mut,
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only)
+    #[account(
+        mut,
+        seeds = [b"vault_token", vault.key().as_ref()],
+        bump = vault.vault_token_bump
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// User's token account receiving the withdrawal (token vaults only)
+    #[account(
+        mut,
+        token::mint = vault.asset_mint
+    )]
+    pub user_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimWithdraw<'info> {
+    pub fn asset_accounts(&self) -> AssetAccounts<'_, 'info> {
+        AssetAccounts {
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
+}
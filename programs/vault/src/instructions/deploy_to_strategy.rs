@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use user_management::{RoleAuthority, PAUSE_REBALANCE};
+use crate::assets::AssetAccounts;
+use crate::state::{Vault, STRATEGY_PROGRAM_ID};
+use crate::errors::VaultError;
+
+/// Send up to `amount` of idle assets to a strategy adapter (strategy program CPI only)
+///
+/// The strategy program signs with its `[b"strategy", vault]` PDA. The amount
+/// is capped by the vault's strategy allocation and by the idle assets left
+/// once fees, processed claims and queued withdraw requests are set aside,
+/// so deploying never starves the withdraw queue. Deployed assets stay in
+/// `total_assets` and are tracked in `deployed_assets`.
+pub fn deploy_to_strategy(ctx: Context<DeployToStrategy>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Err(VaultError::InvalidAmount.into());
+    }
+
+    ctx.accounts.vault.accrue(Clock::get()?.unix_timestamp)?;
+    let vault = &ctx.accounts.vault;
+    let idle = ctx.accounts.asset_accounts().idle_assets()?;
+    let deployed = amount.min(vault.strategy_capacity()).min(idle);
+    if deployed == 0 {
+        return Err(VaultError::StrategyCapacityReached.into());
+    }
+
+    let adapter = ctx.accounts.adapter.to_account_info();
+    ctx.accounts
+        .asset_accounts()
+        .transfer_out(&adapter, ctx.accounts.adapter_token_account.as_ref(), deployed)?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.deployed_assets = vault.deployed_assets.checked_add(deployed)
+        .ok_or(VaultError::OverflowError)?;
+    msg!("Deployed {} to the strategy, {} deployed in total", deployed, vault.deployed_assets);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DeployToStrategy<'info> {
+    /// Vault account
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_REBALANCE) @ VaultError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
+    /// Strategy state PDA of the strategy program, signing through CPI
+    #[account(
+        seeds = [b"strategy", vault.key().as_ref()],
+        seeds::program = STRATEGY_PROGRAM_ID,
+        bump
+    )]
+    pub strategy: Signer<'info>,
+
+    /// Adapter receiving the assets (receives SOL directly for SOL vaults)
+    #[account(mut)]
+    pub adapter: SystemAccount<'info>,
+
+    /// Adapter's token account receiving the assets (token vaults only)
+    #[account(mut)]
+    pub adapter_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Vault SOL account (PDA that holds funds)
+    #[account(
+        mut,
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only)
+    #[account(
+        mut,
+        seeds = [b"vault_token", vault.key().as_ref()],
+        bump = vault.vault_token_bump
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> DeployToStrategy<'info> {
+    pub fn asset_accounts(&self) -> AssetAccounts<'_, 'info> {
+        AssetAccounts {
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
+}
@@ -1,37 +1,100 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+use crate::assets::AssetAccounts;
+use user_management::{RoleAuthority, UserRole, PAUSE_DEPOSITS, ROLE_REGULAR_USER};
 use crate::state::{Vault, UserPosition};
 use crate::errors::VaultError;
 
-pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+pub fn deposit(ctx: Context<Deposit>, amount: u64, min_shares_out: u64) -> Result<()> {
     if amount == 0 {
         return Err(VaultError::InvalidAmount.into());
     }
 
-    let vault = &mut ctx.accounts.vault;
+    let current_time = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault.accrue(current_time)?;
 
-    // Calculate shares to mint
-    let shares = vault.shares_for_deposit(amount);
+    // Calculate shares to mint for the amount left after the entry fee
+    let (shares, fee) = ctx.accounts.vault.quote_deposit(amount)?;
     if shares == 0 {
         return Err(VaultError::InvalidShares.into());
     }
 
-    // Transfer SOL from user to vault
-    system_program::transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            system_program::Transfer {
-                from: ctx.accounts.user.to_account_info(),
-                to: ctx.accounts.vault_account.to_account_info(),
+    // Protect against the share price moving between quote and execution
+    if shares < min_shares_out {
+        return Err(VaultError::SlippageExceeded.into());
+    }
+
+    process_deposit(ctx, amount, shares, fee, current_time)
+}
+
+/// Move `amount` assets in, mint `shares` to the user and update vault state.
+/// `fee` of the amount goes to accumulated fees instead of the share holders.
+pub(crate) fn process_deposit(
+    ctx: Context<Deposit>,
+    amount: u64,
+    shares: u64,
+    fee: u64,
+    current_time: i64,
+) -> Result<()> {
+    // Permissioned vaults only take deposits from holders of the RegularUser role
+    if ctx.accounts.vault.permissioned {
+        let user_role = ctx.accounts.user_role.as_ref().ok_or(VaultError::NotAllowlisted)?;
+        if !user_role.has_role(ROLE_REGULAR_USER) {
+            return Err(VaultError::NotAllowlisted.into());
+        }
+    }
+
+    // Enforce the vault's deposit limits. The per-user cap counts what the
+    // depositor holds now, at the current price, plus the full amount
+    let user_value = ctx
+        .accounts
+        .vault
+        .assets_for_shares(ctx.accounts.user_share_account.amount)?
+        .checked_add(amount)
+        .ok_or(VaultError::OverflowError)?;
+    let total_assets = ctx.accounts.vault.total_assets
+        .checked_add(amount - fee)
+        .ok_or(VaultError::OverflowError)?;
+    ctx.accounts.vault.deposit_limits.check(amount, total_assets, user_value)?;
+
+    // Transfer the underlying asset from user to vault
+    ctx.accounts.asset_accounts().transfer_in(
+        &ctx.accounts.user,
+        ctx.accounts.user_token_account.as_ref(),
+        amount,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+
+    // Mint share tokens to the user, signed by the vault SOL PDA (mint authority)
+    let vault_key = vault.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault_sol",
+        vault_key.as_ref(),
+        &[vault.vault_account_bump],
+    ]];
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                to: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.vault_account.to_account_info(),
             },
+            signer_seeds,
         ),
-        amount,
+        shares,
     )?;
 
     // Update vault state
     vault.total_assets = vault
         .total_assets
-        .checked_add(amount)
+        .checked_add(amount - fee)
+        .ok_or(VaultError::OverflowError)?;
+    vault.accumulated_fees = vault
+        .accumulated_fees
+        .checked_add(fee)
         .ok_or(VaultError::OverflowError)?;
     vault.total_shares = vault
         .total_shares
@@ -44,7 +107,6 @@ pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         // New user
         user_position.user = ctx.accounts.user.key();
         user_position.vault = vault.key();
-        user_position.deposit_timestamp = Clock::get()?.unix_timestamp;
         user_position.total_deposited = amount;
         vault.num_users = vault.num_users.checked_add(1).ok_or(VaultError::OverflowError)?;
     } else {
@@ -55,10 +117,16 @@ pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
             .ok_or(VaultError::OverflowError)?;
     }
 
+    // Shares received by transfer start their time lock now
+    let balance = ctx.accounts.user_share_account.amount;
+    let lock_duration = vault.exit_policy.lock_duration;
+    user_position.track_balance(balance, lock_duration, current_time)?;
     user_position.shares = user_position
         .shares
         .checked_add(shares)
         .ok_or(VaultError::OverflowError)?;
+    // Each deposit is time-locked on its own
+    user_position.add_lot(shares, lock_duration, current_time)?;
     user_position.bump = ctx.bumps.user_position;
 
     Ok(())
@@ -70,14 +138,59 @@ pub struct Deposit<'info> {
     #[account(mut)]
     pub vault: Account<'info, Vault>,
 
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_DEPOSITS) @ VaultError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
     /// User's wallet
     #[account(mut)]
     pub user: Signer<'info>,
 
     /// Vault SOL account (PDA that holds funds)
-    /// In this MVP, we use a simple account. Production would use token accounts.
-    #[account(mut)]
-    pub vault_account: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only)
+    #[account(
+        mut,
+        seeds = [b"vault_token", vault.key().as_ref()],
+        bump = vault.vault_token_bump
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// User's token account to deposit from (token vaults only)
+    #[account(
+        mut,
+        token::mint = vault.asset_mint,
+        token::authority = user
+    )]
+    pub user_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Share (LP) token mint
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: Account<'info, Mint>,
+
+    /// User's share token account that receives minted shares
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = share_mint,
+        associated_token::authority = user
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
 
     /// User position tracking
     #[account(
@@ -89,6 +202,33 @@ pub struct Deposit<'info> {
     )]
     pub user_position: Account<'info, UserPosition>,
 
+    /// Depositor's role for this vault (required by permissioned vaults)
+    #[account(
+        seeds = [b"user_role", vault.key().as_ref(), user.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = user_role.bump
+    )]
+    pub user_role: Option<Account<'info, UserRole>>,
+
     /// System program
     pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+
+    /// Associated token program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> Deposit<'info> {
+    pub fn asset_accounts(&self) -> AssetAccounts<'_, 'info> {
+        AssetAccounts {
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
 }
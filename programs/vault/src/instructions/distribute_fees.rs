@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use user_management::{RoleAuthority, PAUSE_ADMIN, PAUSE_WITHDRAWALS};
+use crate::assets::AssetAccounts;
+use crate::state::{split_fees, Vault};
+use crate::errors::VaultError;
+
+/// Pay the accumulated entry/exit fees out to every recipient in the vault's
+/// fee split table (permissionless keeper call)
+///
+/// Pass one writable remaining account per split, in table order: the
+/// recipient's wallet for SOL vaults, or its token account for token vaults.
+/// Only fees covered by the vault's idle balance are paid; the rest stay
+/// accumulated for a later call.
+pub fn distribute_fees<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DistributeFees<'info>>,
+) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    if vault.fee_splits.is_empty() {
+        return Err(VaultError::InvalidFeeSplit.into());
+    }
+    if ctx.remaining_accounts.len() != vault.fee_splits.len() {
+        return Err(VaultError::InvalidTreasury.into());
+    }
+
+    // Fees are paid from idle custody only, never from assets owed or
+    // reserved for withdraw requests
+    let amount = std::cmp::min(
+        vault.accumulated_fees,
+        ctx.accounts.asset_accounts().fee_liquidity()?,
+    );
+    if amount == 0 {
+        return Err(VaultError::InvalidAmount.into());
+    }
+
+    let parts = split_fees(&vault.fee_splits, amount)?;
+    for ((split, account), part) in vault
+        .fee_splits
+        .iter()
+        .zip(ctx.remaining_accounts.iter())
+        .zip(parts)
+    {
+        // Each account must belong to the recipient its split names
+        let token_account = if vault.is_native() {
+            if account.key() != split.recipient {
+                return Err(VaultError::InvalidTreasury.into());
+            }
+            None
+        } else {
+            let token_account: Account<'info, TokenAccount> = Account::try_from(account)?;
+            if token_account.owner != split.recipient || token_account.mint != vault.asset_mint {
+                return Err(VaultError::InvalidTreasury.into());
+            }
+            Some(token_account)
+        };
+
+        if part > 0 {
+            ctx.accounts
+                .asset_accounts()
+                .transfer_out(account, token_account.as_ref(), part)?;
+        }
+    }
+
+    let vault = &mut ctx.accounts.vault;
+    vault.accumulated_fees -= amount;
+    msg!(
+        "Distributed {} in fees to {} recipients, {} remaining",
+        amount,
+        vault.fee_splits.len(),
+        vault.accumulated_fees
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DistributeFees<'info> {
+    /// Vault account
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_WITHDRAWALS | PAUSE_ADMIN) @ VaultError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
+    /// Vault SOL account (PDA that holds funds)
+    #[account(
+        mut,
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only)
+    #[account(
+        mut,
+        seeds = [b"vault_token", vault.key().as_ref()],
+        bump = vault.vault_token_bump
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> DistributeFees<'info> {
+    pub fn asset_accounts(&self) -> AssetAccounts<'_, 'info> {
+        AssetAccounts {
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
+}
@@ -1,30 +1,82 @@
 use anchor_lang::prelude::*;
-use crate::state::Vault;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+use user_management::{RoleAuthority, UserRole, PAUSE_HARVEST, ROLE_ADMIN};
+use crate::assets::AssetAccounts;
+use crate::state::{split_fees, Vault};
 use crate::errors::VaultError;
 
 /// Harvest yield from strategies (called by authority/keeper)
-/// This simulates the vault earning yield from deployed strategies
-pub fn harvest(ctx: Context<Harvest>, yield_amount: u64) -> Result<()> {
+///
+/// Yield is whatever the vault actually holds beyond its books (holders'
+/// assets, fees and queued claims) - it has to have arrived, so the caller
+/// cannot inflate the share price by stating an amount. Assets deployed to
+/// the strategy count at cost; their gains are realized once recalled.
+///
+/// Performance and management fee shares go to `treasury_share_account`, or,
+/// with a fee split table set, to each recipient by its split: pass one share
+/// token account per split as remaining accounts, in table order.
+pub fn harvest<'info>(ctx: Context<'_, '_, 'info, 'info, Harvest<'info>>) -> Result<()> {
+    let held = ctx
+        .accounts
+        .asset_accounts()
+        .custody_balance()?
+        .checked_add(ctx.accounts.vault.deployed_assets)
+        .ok_or(VaultError::OverflowError)?;
+    let yield_amount = held.saturating_sub(ctx.accounts.vault.booked_assets()?);
     if yield_amount == 0 {
         return Err(VaultError::NoYield.into());
     }
 
-    require_keys_eq!(ctx.accounts.vault.admin, ctx.accounts.admin.key(), VaultError::Unauthorized);
-
+    let current_time = Clock::get()?.unix_timestamp;
     let vault = &mut ctx.accounts.vault;
+    vault.accrue(current_time)?;
+    vault.last_harvest_ts = current_time;
+    vault.last_harvest_yield = yield_amount;
+    msg!("Harvested {} of realized yield", yield_amount);
 
-    // Calculate performance fee (10% of yield)
-    let fee = yield_amount / 10;
-    let net_yield = yield_amount.checked_sub(fee)
-        .ok_or(VaultError::OverflowError)?;
+    vault.book_yield(yield_amount, current_time)?;
 
-    // Update vault state
-    vault.total_yield = vault.total_yield.checked_add(net_yield)
-        .ok_or(VaultError::OverflowError)?;
-    vault.accumulated_fees = vault.accumulated_fees.checked_add(fee)
-        .ok_or(VaultError::OverflowError)?;
-    vault.total_assets = vault.total_assets.checked_add(net_yield)
-        .ok_or(VaultError::OverflowError)?;
+    mint_fee_shares(ctx)
+}
+
+/// Mint the fee shares owed to the fee recipients
+fn mint_fee_shares<'info>(ctx: Context<'_, '_, 'info, 'info, Harvest<'info>>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    let shares = vault.pending_fee_shares;
+    if shares == 0 {
+        return Ok(());
+    }
+
+    if vault.fee_splits.is_empty() {
+        let treasury_share_account = ctx
+            .accounts
+            .treasury_share_account
+            .as_ref()
+            .ok_or(VaultError::InvalidTreasury)?;
+        ctx.accounts.mint_shares(&treasury_share_account.to_account_info(), shares)?;
+    } else {
+        if ctx.remaining_accounts.len() != vault.fee_splits.len() {
+            return Err(VaultError::InvalidTreasury.into());
+        }
+        let parts = split_fees(&vault.fee_splits, shares)?;
+        for ((split, account), part) in vault
+            .fee_splits
+            .iter()
+            .zip(ctx.remaining_accounts.iter())
+            .zip(parts)
+        {
+            // Each account must be a share account of the recipient its split names
+            let share_account: Account<'info, TokenAccount> = Account::try_from(account)?;
+            if share_account.owner != split.recipient || share_account.mint != vault.share_mint {
+                return Err(VaultError::InvalidTreasury.into());
+            }
+            if part > 0 {
+                ctx.accounts.mint_shares(account, part)?;
+            }
+        }
+    }
+
+    ctx.accounts.vault.pending_fee_shares = 0;
 
     Ok(())
 }
@@ -35,6 +87,96 @@ pub struct Harvest<'info> {
     #[account(mut)]
     pub vault: Account<'info, Vault>,
 
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_HARVEST) @ VaultError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
     /// Admin (only admin can harvest)
     pub admin: Signer<'info>,
+
+    /// Admin's role for this vault (must hold Admin)
+    #[account(
+        seeds = [b"user_role", vault.key().as_ref(), admin.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = admin_role.bump,
+        constraint = admin_role.has_role(ROLE_ADMIN) @ VaultError::Unauthorized
+    )]
+    pub admin_role: Account<'info, UserRole>,
+
+    /// Vault SOL account (PDA that holds funds, share mint authority)
+    #[account(
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only)
+    #[account(
+        seeds = [b"vault_token", vault.key().as_ref()],
+        bump = vault.vault_token_bump
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Share (LP) token mint
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: Account<'info, Mint>,
+
+    /// Treasury's share token account receiving fee shares (only when no
+    /// fee split table is set)
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = vault.treasury
+    )]
+    pub treasury_share_account: Option<Account<'info, TokenAccount>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Harvest<'info> {
+    /// Mint `shares` to `to`, signed by the vault SOL PDA (mint authority)
+    fn mint_shares(&self, to: &AccountInfo<'info>, shares: u64) -> Result<()> {
+        let vault_key = self.vault.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault_sol",
+            vault_key.as_ref(),
+            &[self.vault.vault_account_bump],
+        ]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                MintTo {
+                    mint: self.share_mint.to_account_info(),
+                    to: to.clone(),
+                    authority: self.vault_account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            shares,
+        )
+    }
+
+    pub fn asset_accounts(&self) -> AssetAccounts<'_, 'info> {
+        AssetAccounts {
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
 }
@@ -1,17 +1,91 @@
 use anchor_lang::prelude::*;
-use crate::state::Vault;
-use crate::errors::VaultError;
+use anchor_lang::system_program;
+use anchor_spl::token::{Mint, Token};
+use crate::state::{DepositLimits, ExitPolicy, FeeConfig, Vault, DEFAULT_PROFIT_UNLOCK_PERIOD, NATIVE_SOL_DECIMALS};
 
 pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
 
+    init_vault_state(
+        vault,
+        ctx.accounts.admin.key(),
+        ctx.accounts.share_mint.key(),
+        ctx.bumps.vault_account,
+    )?;
+    vault.asset_mint = Pubkey::default();
+    vault.asset_decimals = NATIVE_SOL_DECIMALS;
+    vault.high_water_mark = vault.share_price();
+
+    fund_vault_account(
+        &ctx.accounts.admin,
+        &ctx.accounts.vault_account,
+        &ctx.accounts.system_program,
+    )
+}
+
+/// Set the state shared by SOL and token vaults at creation
+pub(crate) fn init_vault_state(
+    vault: &mut Vault,
+    admin: Pubkey,
+    share_mint: Pubkey,
+    vault_account_bump: u8,
+) -> Result<()> {
     vault.total_assets = 0;
     vault.total_shares = 0;
-    vault.admin = ctx.accounts.admin.key();
+    vault.admin = admin;
     vault.strategy_allocation = 50; // Default 50% allocation
     vault.total_yield = 0;
     vault.accumulated_fees = 0;
     vault.num_users = 0;
+    vault.vault_account_bump = vault_account_bump;
+    vault.share_mint = share_mint;
+    vault.exit_policy = ExitPolicy::default();
+    vault.pending_exit_policy = ExitPolicy::default();
+    vault.pending_exit_policy_eta = 0;
+    vault.withdraw_queue_head = 0;
+    vault.withdraw_queue_tail = 0;
+    vault.queued_withdraw_assets = 0;
+    vault.claimable_withdraw_assets = 0;
+    vault.fee_config = FeeConfig::initial();
+    vault.pending_fee_config = FeeConfig::default();
+    vault.pending_fee_config_eta = 0;
+    vault.last_fee_accrual = Clock::get()?.unix_timestamp;
+    vault.treasury = admin;
+    vault.pending_fee_shares = 0;
+    vault.last_harvest_ts = 0;
+    vault.last_harvest_yield = 0;
+    vault.locked_profit = 0;
+    vault.locked_profit_updated = 0;
+    vault.profit_unlock_end = 0;
+    vault.profit_unlock_period = DEFAULT_PROFIT_UNLOCK_PERIOD;
+    vault.deposit_limits = DepositLimits::default();
+    vault.permissioned = false;
+    vault.fee_splits = Vec::new();
+
+    Ok(())
+}
+
+/// Fund the vault SOL PDA up to the rent-exempt minimum so it persists.
+/// This reserve is never counted in total_assets.
+pub(crate) fn fund_vault_account<'info>(
+    admin: &Signer<'info>,
+    vault_account: &SystemAccount<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let rent_reserve = Rent::get()?.minimum_balance(0);
+    let shortfall = rent_reserve.saturating_sub(vault_account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: admin.to_account_info(),
+                    to: vault_account.to_account_info(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
 
     Ok(())
 }
@@ -22,10 +96,32 @@ pub struct Initialize<'info> {
     #[account(init, payer = admin, space = Vault::LEN)]
     pub vault: Account<'info, Vault>,
 
+    /// Vault SOL account (PDA that holds deposited lamports)
+    #[account(
+        mut,
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Share (LP) token mint, minted on deposit and burned on withdraw
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"share_mint", vault.key().as_ref()],
+        bump,
+        mint::decimals = NATIVE_SOL_DECIMALS,
+        mint::authority = vault_account,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
     /// Admin/owner of the vault
     #[account(mut)]
     pub admin: Signer<'info>,
 
     /// System program for account creation
     pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
 }
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::state::Vault;
+use super::initialize::{fund_vault_account, init_vault_state};
+
+/// Initialize a vault denominated in an SPL token (e.g. USDC, mSOL)
+pub fn initialize_token_vault(ctx: Context<InitializeTokenVault>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+
+    init_vault_state(
+        vault,
+        ctx.accounts.admin.key(),
+        ctx.accounts.share_mint.key(),
+        ctx.bumps.vault_account,
+    )?;
+    vault.asset_mint = ctx.accounts.asset_mint.key();
+    vault.asset_decimals = ctx.accounts.asset_mint.decimals;
+    vault.high_water_mark = vault.share_price();
+    vault.vault_token_bump = ctx.bumps.vault_token_account;
+
+    fund_vault_account(
+        &ctx.accounts.admin,
+        &ctx.accounts.vault_account,
+        &ctx.accounts.system_program,
+    )
+}
+
+#[derive(Accounts)]
+pub struct InitializeTokenVault<'info> {
+    /// Vault account to initialize
+    #[account(init, payer = admin, space = Vault::LEN)]
+    pub vault: Account<'info, Vault>,
+
+    /// Vault authority PDA - owns the token account and mints shares
+    #[account(
+        mut,
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint the vault is denominated in
+    pub asset_mint: Account<'info, Mint>,
+
+    /// Token account (PDA) that holds the vault's underlying assets
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"vault_token", vault.key().as_ref()],
+        bump,
+        token::mint = asset_mint,
+        token::authority = vault_account,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Share (LP) token mint, using the asset's decimals
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"share_mint", vault.key().as_ref()],
+        bump,
+        mint::decimals = asset_mint.decimals,
+        mint::authority = vault_account,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// Admin/owner of the vault
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// System program for account creation
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+use crate::errors::VaultError;
+use super::deposit::{process_deposit, Deposit};
+
+/// Mint an exact number of shares, paying at most `max_assets_in`
+pub fn mint_shares(ctx: Context<Deposit>, shares: u64, max_assets_in: u64) -> Result<()> {
+    if shares == 0 {
+        return Err(VaultError::InvalidShares.into());
+    }
+
+    let current_time = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault.accrue(current_time)?;
+
+    // Assets required include the entry fee and are rounded up so the vault
+    // is never short-changed
+    let (amount, fee) = ctx.accounts.vault.quote_mint(shares)?;
+    if amount == 0 {
+        return Err(VaultError::InvalidAmount.into());
+    }
+
+    if amount > max_assets_in {
+        return Err(VaultError::SlippageExceeded.into());
+    }
+
+    process_deposit(ctx, amount, shares, fee, current_time)
+}
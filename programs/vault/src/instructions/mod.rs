@@ -1,13 +1,37 @@
 pub mod initialize;
+pub mod initialize_token_vault;
 pub mod deposit;
+pub mod mint_shares;
 pub mod withdraw;
+pub mod withdraw_assets;
+pub mod request_withdraw;
+pub mod process_withdraw_queue;
+pub mod claim_withdraw;
 pub mod harvest;
 pub mod withdraw_fees;
+pub mod distribute_fees;
+pub mod deploy_to_strategy;
+pub mod recall_from_strategy;
 pub mod update_settings;
+pub mod apply_fee_config;
+pub mod apply_exit_policy;
+pub mod preview;
 
 pub use initialize::*;
+pub use initialize_token_vault::*;
 pub use deposit::*;
+pub use mint_shares::*;
 pub use withdraw::*;
+pub use withdraw_assets::*;
+pub use request_withdraw::*;
+pub use process_withdraw_queue::*;
+pub use claim_withdraw::*;
 pub use harvest::*;
 pub use withdraw_fees::*;
+pub use distribute_fees::*;
+pub use deploy_to_strategy::*;
+pub use recall_from_strategy::*;
 pub use update_settings::*;
+pub use apply_fee_config::*;
+pub use apply_exit_policy::*;
+pub use preview::*;
@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use user_management::{RoleAuthority, UserRole, PAUSE_DEPOSITS, PAUSE_WITHDRAWALS, ROLE_REGULAR_USER};
+use crate::assets::AssetAccounts;
+use crate::state::{bps_of, PenaltyCurve, Vault, UserPosition};
+
+// Read-only quotes in the style of ERC-4626. Each returns its value through
+// Anchor return data so clients and composing programs can simulate them.
+// Quotes include entry/exit fees, the management fee accrued so far and
+// profit unlocked so far.
+
+/// Shares minted for depositing `amount` assets
+pub fn preview_deposit(ctx: Context<Preview>, amount: u64) -> Result<u64> {
+    Ok(accrued_vault(&ctx.accounts.vault)?.quote_deposit(amount)?.0)
+}
+
+/// Assets required to mint exactly `shares`
+pub fn preview_mint(ctx: Context<Preview>, shares: u64) -> Result<u64> {
+    Ok(accrued_vault(&ctx.accounts.vault)?.quote_mint(shares)?.0)
+}
+
+/// Shares burned to withdraw exactly `amount` assets
+pub fn preview_withdraw(ctx: Context<Preview>, amount: u64) -> Result<u64> {
+    Ok(accrued_vault(&ctx.accounts.vault)?.quote_withdraw(amount)?.0)
+}
+
+/// Assets received for redeeming `shares`
+pub fn preview_redeem(ctx: Context<Preview>, shares: u64) -> Result<u64> {
+    Ok(accrued_vault(&ctx.accounts.vault)?.quote_redeem(shares)?.0)
+}
+
+/// Largest deposit `user` can make right now, under the TVL cap and their
+/// per-user cap; 0 while deposits are paused or the user is not allowlisted
+pub fn max_deposit(ctx: Context<MaxDeposit>) -> Result<u64> {
+    let vault = &ctx.accounts.vault;
+    if ctx.accounts.role_authority.is_paused(PAUSE_DEPOSITS) {
+        return Ok(0);
+    }
+    if vault.permissioned {
+        let allowed = ctx.accounts.user_role.as_ref()
+            .is_some_and(|user_role| user_role.has_role(ROLE_REGULAR_USER));
+        if !allowed {
+            return Ok(0);
+        }
+    }
+
+    // The per-user cap counts what the user holds now plus the full amount
+    // deposited, fee included
+    let vault = &accrued_vault(vault)?;
+    let held_shares = ctx.accounts.user_share_account.as_ref().map_or(0, |account| account.amount);
+    let max = vault.tvl_headroom().min(vault.user_cap_headroom(held_shares)?);
+
+    if max < vault.deposit_limits.min_deposit {
+        return Ok(0);
+    }
+    Ok(max)
+}
+
+/// Largest amount of assets the share holder can withdraw right now, net of
+/// any early-exit penalty and exit fee and capped by the vault's idle
+/// assets; 0 while withdrawals are paused
+pub fn max_withdraw(ctx: Context<MaxWithdraw>) -> Result<u64> {
+    if ctx.accounts.role_authority.is_paused(PAUSE_WITHDRAWALS) {
+        return Ok(0);
+    }
+
+    let vault = &accrued_vault(&ctx.accounts.vault)?;
+    let balance = ctx.accounts.user_share_account.amount;
+
+    // Without a position the whole balance is untracked (received by transfer)
+    let user_position = ctx.accounts.user_position.as_deref().cloned().unwrap_or_default();
+    let current_time = Clock::get()?.unix_timestamp;
+    let policy = &vault.exit_policy;
+    let shares = if policy.curve == PenaltyCurve::Locked {
+        user_position.withdrawable_shares(balance, policy.lock_duration, current_time)
+    } else {
+        balance - user_position.exit_penalty(balance, balance, policy, current_time)?
+    };
+
+    if shares == 0 {
+        return Ok(0);
+    }
+    let (assets, fee) = vault.quote_redeem(shares)?;
+
+    // An instant withdrawal pays the assets and the exit fee from idle assets
+    let idle = ctx.accounts.asset_accounts().idle_assets()?;
+    if assets + fee > idle {
+        return Ok(idle - bps_of(idle, vault.fee_config.withdrawal_fee_bps)?);
+    }
+    Ok(assets)
+}
+
+/// Copy of the vault as the next state-changing instruction will see it
+fn accrued_vault(vault: &Vault) -> Result<Vault> {
+    let mut vault = vault.clone();
+    vault.accrue(Clock::get()?.unix_timestamp)?;
+    Ok(vault)
+}
+
+#[derive(Accounts)]
+pub struct Preview<'info> {
+    /// Vault account
+    pub vault: Account<'info, Vault>,
+}
+
+#[derive(Accounts)]
+pub struct MaxDeposit<'info> {
+    /// Vault account
+    pub vault: Account<'info, Vault>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
+    /// CHECK: Depositor being quoted, only used to derive their PDAs
+    pub user: UncheckedAccount<'info>,
+
+    /// Depositor's share token account, if they hold shares (counts toward
+    /// the per-user cap)
+    #[account(
+        associated_token::mint = vault.share_mint,
+        associated_token::authority = user
+    )]
+    pub user_share_account: Option<Account<'info, TokenAccount>>,
+
+    /// Depositor's role for this vault (required by permissioned vaults)
+    #[account(
+        seeds = [b"user_role", vault.key().as_ref(), user.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = user_role.bump
+    )]
+    pub user_role: Option<Account<'info, UserRole>>,
+}
+
+#[derive(Accounts)]
+pub struct MaxWithdraw<'info> {
+    /// Vault account
+    pub vault: Account<'info, Vault>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
+    /// Share token account of the holder being quoted
+    #[account(token::mint = vault.share_mint)]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    /// Holder's position, if they have deposited (carries the time lock)
+    #[account(
+        seeds = [b"user_position", vault.key().as_ref(), user_share_account.owner.as_ref()],
+        bump = user_position.bump
+    )]
+    pub user_position: Option<Account<'info, UserPosition>>,
+
+    /// Vault SOL account (PDA that holds funds)
+    #[account(
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only)
+    #[account(
+        seeds = [b"vault_token", vault.key().as_ref()],
+        bump = vault.vault_token_bump
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> MaxWithdraw<'info> {
+    pub fn asset_accounts(&self) -> AssetAccounts<'_, 'info> {
+        AssetAccounts {
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
+}
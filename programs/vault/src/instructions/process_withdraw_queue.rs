@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+use user_management::{RoleAuthority, PAUSE_WITHDRAWALS};
+use crate::assets::AssetAccounts;
+use crate::state::{Vault, WithdrawRequest};
+use crate::errors::VaultError;
+
+/// Fill queued withdraw requests in FIFO order (permissionless keeper call)
+///
+/// Pass the requests as writable remaining accounts, starting at the head of
+/// the queue. Each request burns its escrowed shares at the current price and
+/// sets the assets, net of the exit fee, aside for `claim_withdraw`.
+/// Processing stops at the first request the vault's idle assets cannot
+/// cover, so later requests never jump ahead of an earlier one. When capital
+/// is deployed, the keeper first calls the strategy's `unwind` to recall the
+/// shortfall.
+pub fn process_withdraw_queue<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProcessWithdrawQueue<'info>>,
+) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    ctx.accounts.vault.accrue(Clock::get()?.unix_timestamp)?;
+    let mut available = ctx.accounts.asset_accounts().available_assets()?;
+    let mut processed = 0u64;
+
+    for account in ctx.remaining_accounts.iter() {
+        let mut request: Account<'info, WithdrawRequest> = Account::try_from(account)?;
+        let vault = &ctx.accounts.vault;
+        if request.vault != vault_key || request.id != vault.withdraw_queue_head {
+            return Err(VaultError::InvalidWithdrawRequest.into());
+        }
+
+        // Penalty shares are burned without payout, crediting remaining holders
+        let net_shares = request.shares - request.penalty_shares;
+        let (assets, fee) = vault.quote_redeem(net_shares)?;
+        let gross_assets = assets + fee;
+        if gross_assets > available {
+            break;
+        }
+
+        // Burn the escrowed shares, signed by the vault SOL PDA (escrow authority)
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault_sol",
+            vault_key.as_ref(),
+            &[vault.vault_account_bump],
+        ]];
+        token::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.withdraw_escrow.to_account_info(),
+                    authority: ctx.accounts.vault_account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            request.shares,
+        )?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.total_assets = vault.total_assets.checked_sub(gross_assets)
+            .ok_or(VaultError::OverflowError)?;
+        vault.accumulated_fees = vault.accumulated_fees.checked_add(fee)
+            .ok_or(VaultError::OverflowError)?;
+        vault.total_shares = vault.total_shares.checked_sub(request.shares)
+            .ok_or(VaultError::OverflowError)?;
+        vault.queued_withdraw_assets = vault.queued_withdraw_assets.checked_sub(request.reserved_assets)
+            .ok_or(VaultError::OverflowError)?;
+        vault.claimable_withdraw_assets = vault.claimable_withdraw_assets.checked_add(assets)
+            .ok_or(VaultError::OverflowError)?;
+        vault.withdraw_queue_head += 1;
+
+        request.assets = assets;
+        request.fulfilled = true;
+        request.exit(&crate::ID)?;
+
+        available -= gross_assets;
+        processed += 1;
+    }
+
+    msg!(
+        "Processed {} withdraw requests, {} still queued",
+        processed,
+        ctx.accounts.vault.withdraw_queue_depth()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProcessWithdrawQueue<'info> {
+    /// Vault account
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_WITHDRAWALS) @ VaultError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
+    /// Vault SOL account (PDA that holds funds)
+    #[account(
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only)
+    #[account(
+        seeds = [b"vault_token", vault.key().as_ref()],
+        bump = vault.vault_token_bump
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Share (LP) token mint
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: Account<'info, Mint>,
+
+    /// Escrow holding the shares of queued requests
+    #[account(
+        mut,
+        seeds = [b"withdraw_escrow", vault.key().as_ref()],
+        bump
+    )]
+    pub withdraw_escrow: Account<'info, TokenAccount>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ProcessWithdrawQueue<'info> {
+    pub fn asset_accounts(&self) -> AssetAccounts<'_, 'info> {
+        AssetAccounts {
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
+}
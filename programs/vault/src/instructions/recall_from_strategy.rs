@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::assets::AssetAccounts;
+use crate::state::{Vault, STRATEGY_PROGRAM_ID};
+use crate::errors::VaultError;
+
+/// Return `amount` from a strategy adapter to the vault (strategy program CPI only)
+///
+/// The strategy program signs with its `[b"strategy", vault]` PDA and the
+/// adapter's PDA. `principal` is the deployed cost the strategy books as
+/// settled and comes off `deployed_assets`. Anything of `amount` above it is
+/// strategy profit, realized by the next `harvest`; a shortfall below it
+/// (closing a position at a loss) is realized as a loss right away. Never
+/// paused, so assets can always be brought home.
+pub fn recall_from_strategy(
+    ctx: Context<RecallFromStrategy>,
+    amount: u64,
+    principal: u64,
+) -> Result<()> {
+    if amount == 0 && principal == 0 {
+        return Err(VaultError::InvalidAmount.into());
+    }
+
+    if amount > 0 {
+        ctx.accounts.asset_accounts().transfer_in(
+            &ctx.accounts.adapter,
+            ctx.accounts.adapter_token_account.as_ref(),
+            amount,
+        )?;
+    }
+
+    let vault = &mut ctx.accounts.vault;
+    vault.deployed_assets = vault.deployed_assets.saturating_sub(principal);
+    if amount < principal {
+        vault.accrue(Clock::get()?.unix_timestamp)?;
+        vault.realize_loss(principal - amount);
+        msg!("Realized a strategy loss of {}", principal - amount);
+    }
+    msg!("Recalled {} from the strategy, {} still deployed", amount, vault.deployed_assets);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RecallFromStrategy<'info> {
+    /// Vault account
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    /// Strategy state PDA of the strategy program, signing through CPI
+    #[account(
+        seeds = [b"strategy", vault.key().as_ref()],
+        seeds::program = STRATEGY_PROGRAM_ID,
+        bump
+    )]
+    pub strategy: Signer<'info>,
+
+    /// Adapter returning the assets (sends SOL directly for SOL vaults,
+    /// authority of its token account for token vaults)
+    #[account(mut)]
+    pub adapter: Signer<'info>,
+
+    /// Adapter's token account returning the assets (token vaults only)
+    #[account(mut)]
+    pub adapter_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Vault SOL account (PDA that holds funds)
+    #[account(
+        mut,
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only)
+    #[account(
+        mut,
+        seeds = [b"vault_token", vault.key().as_ref()],
+        bump = vault.vault_token_bump
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> RecallFromStrategy<'info> {
+    pub fn asset_accounts(&self) -> AssetAccounts<'_, 'info> {
+        AssetAccounts {
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
+}
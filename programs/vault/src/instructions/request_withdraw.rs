@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use user_management::{RoleAuthority, PAUSE_WITHDRAWALS};
+use crate::state::{Vault, UserPosition, WithdrawRequest};
+use crate::errors::VaultError;
+use super::withdraw::exit_penalty;
+
+/// Queue a withdrawal of `shares` for when the vault has the assets idle
+///
+/// The shares move into the vault's escrow and keep earning until the request
+/// is processed; the assets paid out are priced at processing time. Until
+/// then the current value of the shares kept after the penalty is reserved,
+/// so instant withdrawals cannot take it.
+pub fn request_withdraw(ctx: Context<RequestWithdraw>, shares: u64) -> Result<()> {
+    if shares == 0 {
+        return Err(VaultError::InvalidShares.into());
+    }
+
+    // Time lock and early-exit penalty apply as for an instant withdrawal
+    let current_time = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault.accrue(current_time)?;
+    let balance = ctx.accounts.user_share_account.amount;
+    let penalty = exit_penalty(
+        &ctx.accounts.vault,
+        &ctx.accounts.user_position,
+        shares,
+        balance,
+        current_time,
+    )?;
+
+    let vault_key = ctx.accounts.vault.key();
+    let lock_duration = ctx.accounts.vault.exit_policy.lock_duration;
+    let user_position = &mut ctx.accounts.user_position;
+    if user_position.user == Pubkey::default() {
+        // Position opened by a transfer recipient - its balance is not tracked yet
+        user_position.user = ctx.accounts.user.key();
+        user_position.vault = vault_key;
+        user_position.bump = ctx.bumps.user_position;
+    }
+    user_position.consume(shares, balance, lock_duration, current_time)?;
+
+    // Lock the shares in escrow until the request is processed
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_share_account.to_account_info(),
+                to: ctx.accounts.withdraw_escrow.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    let request = &mut ctx.accounts.withdraw_request;
+    request.vault = vault_key;
+    request.user = ctx.accounts.user.key();
+    request.id = vault.withdraw_queue_tail;
+    request.shares = shares;
+    request.penalty_shares = penalty;
+    request.reserved_assets = vault.reserve_withdraw(shares - penalty)?;
+    request.assets = 0;
+    request.fulfilled = false;
+    request.requested_at = current_time;
+    request.bump = ctx.bumps.withdraw_request;
+
+    vault.withdraw_queue_tail = vault
+        .withdraw_queue_tail
+        .checked_add(1)
+        .ok_or(VaultError::OverflowError)?;
+
+    msg!("Withdraw request {} queued ({} ahead)", request.id, vault.withdraw_queue_depth() - 1);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RequestWithdraw<'info> {
+    /// Vault account
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_WITHDRAWALS) @ VaultError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
+    /// User's wallet
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Vault SOL account (PDA, authority of the share escrow)
+    #[account(
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Share (LP) token mint
+    #[account(address = vault.share_mint)]
+    pub share_mint: Account<'info, Mint>,
+
+    /// User's share token account to lock shares from
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = user
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    /// Escrow holding the shares of queued requests
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"withdraw_escrow", vault.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = vault_account
+    )]
+    pub withdraw_escrow: Account<'info, TokenAccount>,
+
+    /// New request, at the tail of the vault's queue
+    #[account(
+        init,
+        payer = user,
+        space = WithdrawRequest::LEN,
+        seeds = [b"withdraw_request", vault.key().as_ref(), &vault.withdraw_queue_tail.to_le_bytes()],
+        bump
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    /// User position (created for holders who received shares by transfer)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::LEN,
+        seeds = [b"user_position", vault.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
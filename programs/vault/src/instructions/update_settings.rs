@@ -1,25 +1,103 @@
 use anchor_lang::prelude::*;
-use crate::state::Vault;
+use user_management::{RoleAuthority, UserRole, PAUSE_ADMIN, ROLE_ADMIN};
+use crate::state::{
+    validate_fee_splits, DepositLimits, ExitPolicy, FeeConfig, FeeSplit, Vault, FEE_UPDATE_DELAY,
+    MAX_PROFIT_UNLOCK_PERIOD,
+};
 use crate::errors::VaultError;
 
+/// Settings to change - each `None` leaves the current value in place
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct SettingsUpdate {
+    /// Strategy allocation (0-100%)
+    pub strategy_allocation: Option<u8>,
+    /// Deposit lock and early-exit penalty, applied after `FEE_UPDATE_DELAY`
+    pub exit_policy: Option<ExitPolicy>,
+    /// New fees, applied after `FEE_UPDATE_DELAY`
+    pub fee_config: Option<FeeConfig>,
+    /// Time each harvest's profit takes to unlock
+    pub profit_unlock_period: Option<i64>,
+    /// TVL cap, per-user cap and minimum deposit
+    pub deposit_limits: Option<DepositLimits>,
+    /// Whether deposits require the RegularUser role
+    pub permissioned: Option<bool>,
+    /// Recipient of withdrawn fees and fee shares
+    pub treasury: Option<Pubkey>,
+    /// Fee split table for `distribute_fees` (empty to stop splitting)
+    pub fee_splits: Option<Vec<FeeSplit>>,
+}
+
 /// Update vault settings (Admin role)
-pub fn update_settings(
-    ctx: Context<UpdateSettings>,
-    strategy_allocation: Option<u8>,
-) -> Result<()> {
+pub fn update_settings(ctx: Context<UpdateSettings>, settings: SettingsUpdate) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
 
-    // Verify caller is vault admin (for now, without RBAC integration)
-    require_keys_eq!(vault.admin, ctx.accounts.admin.key(), VaultError::Unauthorized);
-
     // Update strategy allocation if provided
-    if let Some(allocation) = strategy_allocation {
+    if let Some(allocation) = settings.strategy_allocation {
         if allocation > 100 {
             return Err(VaultError::InvalidAllocation.into());
         }
         vault.strategy_allocation = allocation;
     }
 
+    // Schedule a new lock and early-exit penalty - it applies to existing lots
+    // too, so holders get the same timelock to exit as for a fee change
+    if let Some(policy) = settings.exit_policy {
+        policy.validate()?;
+        let eta = Clock::get()?
+            .unix_timestamp
+            .checked_add(FEE_UPDATE_DELAY)
+            .ok_or(VaultError::OverflowError)?;
+        vault.pending_exit_policy = policy;
+        vault.pending_exit_policy_eta = eta;
+        msg!("Exit policy change scheduled, can be applied after {}", eta);
+    }
+
+    // Schedule new fees - they take effect through `apply_fee_config` once the
+    // timelock has passed, replacing any change still pending
+    if let Some(fees) = settings.fee_config {
+        fees.validate()?;
+        let eta = Clock::get()?
+            .unix_timestamp
+            .checked_add(FEE_UPDATE_DELAY)
+            .ok_or(VaultError::OverflowError)?;
+        vault.pending_fee_config = fees;
+        vault.pending_fee_config_eta = eta;
+        msg!("Fee change scheduled, can be applied after {}", eta);
+    }
+
+    // Update how long harvested profit takes to unlock (applies from the next harvest)
+    if let Some(period) = settings.profit_unlock_period {
+        if !(0..=MAX_PROFIT_UNLOCK_PERIOD).contains(&period) {
+            return Err(VaultError::InvalidUnlockPeriod.into());
+        }
+        vault.profit_unlock_period = period;
+    }
+
+    // Update TVL cap, per-user cap and minimum deposit if provided
+    if let Some(limits) = settings.deposit_limits {
+        limits.validate()?;
+        vault.deposit_limits = limits;
+    }
+
+    // Open or close deposits to holders of the RegularUser role only
+    if let Some(permissioned) = settings.permissioned {
+        vault.permissioned = permissioned;
+    }
+
+    // Redirect fees to a new recipient
+    if let Some(treasury) = settings.treasury {
+        if treasury == Pubkey::default() {
+            return Err(VaultError::InvalidTreasury.into());
+        }
+        vault.treasury = treasury;
+    }
+
+    // Replace the fee split table if provided
+    if let Some(splits) = settings.fee_splits {
+        validate_fee_splits(&splits)?;
+        vault.fee_splits = splits;
+    }
+
     Ok(())
 }
 
@@ -29,6 +107,24 @@ pub struct UpdateSettings<'info> {
     #[account(mut)]
     pub vault: Account<'info, Vault>,
 
-    /// Admin (must match vault.admin)
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_ADMIN) @ VaultError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
+    /// Admin
     pub admin: Signer<'info>,
+
+    /// Admin's role for this vault (must hold Admin)
+    #[account(
+        seeds = [b"user_role", vault.key().as_ref(), admin.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = admin_role.bump,
+        constraint = admin_role.has_role(ROLE_ADMIN) @ VaultError::Unauthorized
+    )]
+    pub admin_role: Account<'info, UserRole>,
 }
@@ -1,59 +1,98 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
-use crate::state::{Vault, UserPosition};
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+use user_management::{RoleAuthority, PAUSE_WITHDRAWALS};
+use crate::assets::AssetAccounts;
+use crate::state::{PenaltyCurve, Vault, UserPosition};
 use crate::errors::VaultError;
 
-const TIME_LOCK_DURATION: i64 = 24 * 60 * 60; // 24 hours
-
-pub fn withdraw(ctx: Context<Withdraw>, shares: u64) -> Result<()> {
+/// Redeem `shares` for the vault asset
+///
+/// Only `PAUSE_WITHDRAWALS` stops this - an instant withdrawal pays out idle
+/// assets alone, so users can still exit while deposits, harvests or
+/// rebalancing are paused. Assets owed to queued withdraw requests are not
+/// idle: the queue is paid first.
+pub fn withdraw(ctx: Context<Withdraw>, shares: u64, min_assets_out: u64) -> Result<()> {
     if shares == 0 {
         return Err(VaultError::InvalidShares.into());
     }
 
-    let vault = &mut ctx.accounts.vault;
-    let user_position = &mut ctx.accounts.user_position;
-
-    // Check time lock (prevent immediate withdrawal)
     let current_time = Clock::get()?.unix_timestamp;
-    let time_since_deposit = current_time.checked_sub(user_position.deposit_timestamp)
-        .ok_or(VaultError::OverflowError)?;
+    ctx.accounts.vault.accrue(current_time)?;
 
-    if time_since_deposit < TIME_LOCK_DURATION {
-        return Err(VaultError::TimeLockActive.into());
+    // Shares given up to the early-exit penalty are burned without payout
+    let penalty = ctx.accounts.exit_penalty(shares, current_time)?;
+
+    // Calculate assets to withdraw, net of the exit fee
+    let (assets, fee) = ctx.accounts.vault.quote_redeem(shares - penalty)?;
+    if assets == 0 {
+        return Err(VaultError::InvalidAmount.into());
     }
 
-    // Verify user has enough shares
-    if shares > user_position.shares {
-        return Err(VaultError::InsufficientBalance.into());
+    // Protect against the share price moving between quote and execution
+    if assets < min_assets_out {
+        return Err(VaultError::SlippageExceeded.into());
     }
 
-    // Calculate assets to withdraw
-    let assets = vault.assets_for_shares(shares);
-    if assets == 0 {
-        return Err(VaultError::InvalidAmount.into());
+    process_withdraw(ctx, shares, assets, fee, current_time)
+}
+
+/// Burn `shares` from the user, pay out `assets`, keep `fee` and update vault state
+///
+/// Any difference between `shares` and the shares worth `assets + fee` is the
+/// early-exit penalty: its assets stay in the vault for the remaining holders.
+pub(crate) fn process_withdraw(
+    ctx: Context<Withdraw>,
+    shares: u64,
+    assets: u64,
+    fee: u64,
+    current_time: i64,
+) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let lock_duration = ctx.accounts.vault.exit_policy.lock_duration;
+    let balance = ctx.accounts.user_share_account.amount;
+    let user_position = &mut ctx.accounts.user_position;
+
+    if user_position.user == Pubkey::default() {
+        // Position opened by a transfer recipient - its balance is not tracked yet
+        user_position.user = ctx.accounts.user.key();
+        user_position.vault = vault_key;
+        user_position.bump = ctx.bumps.user_position;
     }
+    user_position.consume(shares, balance, lock_duration, current_time)?;
 
-    // Transfer SOL back to user
-    // In a real implementation, this would use a PDA signer
-    system_program::transfer(
+    // Idle assets only - the rest is deployed or owed to queued withdrawals
+    let gross_assets = assets.checked_add(fee).ok_or(VaultError::OverflowError)?;
+    if gross_assets > ctx.accounts.asset_accounts().idle_assets()? {
+        return Err(VaultError::InsufficientLiquidity.into());
+    }
+
+    // Burn the redeemed share tokens
+    token::burn(
         CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            system_program::Transfer {
-                from: ctx.accounts.vault_account.to_account_info(),
-                to: ctx.accounts.user.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
             },
         ),
+        shares,
+    )?;
+
+    // Pay the underlying asset back to the user, signed by the vault SOL PDA
+    ctx.accounts.asset_accounts().transfer_out(
+        &ctx.accounts.user.to_account_info(),
+        ctx.accounts.user_token_account.as_ref(),
         assets,
     )?;
 
     // Update vault state
-    vault.total_assets = vault.total_assets.checked_sub(assets)
+    let vault = &mut ctx.accounts.vault;
+    vault.total_assets = vault.total_assets.checked_sub(gross_assets)
         .ok_or(VaultError::OverflowError)?;
-    vault.total_shares = vault.total_shares.checked_sub(shares)
+    vault.accumulated_fees = vault.accumulated_fees.checked_add(fee)
         .ok_or(VaultError::OverflowError)?;
-
-    // Update user position
-    user_position.shares = user_position.shares.checked_sub(shares)
+    vault.total_shares = vault.total_shares.checked_sub(shares)
         .ok_or(VaultError::OverflowError)?;
 
     Ok(())
@@ -65,22 +104,152 @@ pub struct Withdraw<'info> {
     #[account(mut)]
     pub vault: Account<'info, Vault>,
 
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_WITHDRAWALS) @ VaultError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
     /// User's wallet
     #[account(mut)]
     pub user: Signer<'info>,
 
-    /// Vault SOL account
-    #[account(mut)]
-    pub vault_account: AccountInfo<'info>,
+    /// Vault SOL account (PDA that holds funds)
+    #[account(
+        mut,
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
+
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
 
-    /// User position
+    /// Vault token account holding the underlying asset (token vaults only)
     #[account(
         mut,
+        seeds = [b"vault_token", vault.key().as_ref()],
+        bump = vault.vault_token_bump
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// User's token account receiving the withdrawal (token vaults only)
+    #[account(
+        mut,
+        token::mint = vault.asset_mint
+    )]
+    pub user_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Share (LP) token mint
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: Account<'info, Mint>,
+
+    /// User's share token account to burn from
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = user
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+
+    /// User position (created for holders who received shares by transfer)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::LEN,
         seeds = [b"user_position", vault.key().as_ref(), user.key().as_ref()],
-        bump = user_position.bump
+        bump
     )]
     pub user_position: Account<'info, UserPosition>,
 
     /// System program
     pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Withdraw<'info> {
+    /// Early-exit penalty, in shares, for burning `shares` right now
+    pub fn exit_penalty(&self, shares: u64, current_time: i64) -> Result<u64> {
+        exit_penalty(
+            &self.vault,
+            &self.user_position,
+            shares,
+            self.user_share_account.amount,
+            current_time,
+        )
+    }
+
+    /// Shares to burn so that `net` shares are redeemed after the early-exit penalty
+    pub fn shares_for_net(&self, net: u64, current_time: i64) -> Result<u64> {
+        let balance = self.user_share_account.amount;
+        if net > balance {
+            return Err(VaultError::InsufficientBalance.into());
+        }
+
+        check_time_lock(&self.vault, &self.user_position, net, balance, current_time)?;
+        let shares = self.user_position.shares_for_net(
+            net,
+            balance,
+            &self.vault.exit_policy,
+            current_time,
+        )?;
+        if shares > net {
+            msg!("Early exit penalty: {} of {} shares", shares - net, shares);
+        }
+        Ok(shares)
+    }
+
+    pub fn asset_accounts(&self) -> AssetAccounts<'_, 'info> {
+        AssetAccounts {
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
+}
+
+/// Early-exit penalty, in shares, for burning `shares` out of `balance` right now
+pub(crate) fn exit_penalty(
+    vault: &Vault,
+    user_position: &UserPosition,
+    shares: u64,
+    balance: u64,
+    current_time: i64,
+) -> Result<u64> {
+    if shares > balance {
+        return Err(VaultError::InsufficientBalance.into());
+    }
+
+    check_time_lock(vault, user_position, shares, balance, current_time)?;
+    let penalty = user_position.exit_penalty(shares, balance, &vault.exit_policy, current_time)?;
+    if penalty > 0 {
+        msg!("Early exit penalty: {} of {} shares", penalty, shares);
+    }
+    Ok(penalty)
+}
+
+/// Reject locked shares outright when the vault allows no early exit
+fn check_time_lock(
+    vault: &Vault,
+    user_position: &UserPosition,
+    shares: u64,
+    balance: u64,
+    current_time: i64,
+) -> Result<()> {
+    let policy = &vault.exit_policy;
+    let withdrawable = user_position.withdrawable_shares(balance, policy.lock_duration, current_time);
+    if shares > withdrawable && policy.curve == PenaltyCurve::Locked {
+        msg!("Time lock active: {} of {} shares are currently withdrawable", withdrawable, balance);
+        return Err(VaultError::TimeLockActive.into());
+    }
+    Ok(())
 }
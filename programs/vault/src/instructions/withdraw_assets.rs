@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+use crate::errors::VaultError;
+use super::withdraw::{process_withdraw, Withdraw};
+
+/// Withdraw an exact asset amount, burning at most `max_shares_burned`
+pub fn withdraw_assets(ctx: Context<Withdraw>, amount: u64, max_shares_burned: u64) -> Result<()> {
+    if amount == 0 {
+        return Err(VaultError::InvalidAmount.into());
+    }
+
+    let current_time = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault.accrue(current_time)?;
+
+    // Shares redeemed cover the exit fee too and are rounded up so the vault
+    // is never short-changed
+    let (net, fee) = ctx.accounts.vault.quote_withdraw(amount)?;
+    if net == 0 {
+        return Err(VaultError::InvalidShares.into());
+    }
+
+    // Burn extra shares to cover any early-exit penalty
+    let shares = ctx.accounts.shares_for_net(net, current_time)?;
+
+    if shares > max_shares_burned {
+        return Err(VaultError::SlippageExceeded.into());
+    }
+
+    process_withdraw(ctx, shares, amount, fee, current_time)
+}
@@ -1,33 +1,49 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use user_management::{RoleAuthority, UserRole, PAUSE_ADMIN, PAUSE_WITHDRAWALS, ROLE_TREASURY};
+use crate::assets::AssetAccounts;
 use crate::state::Vault;
 use crate::errors::VaultError;
 
-/// Withdraw accumulated performance fees (Treasury role)
-pub fn withdraw_fees(ctx: Context<WithdrawFees>) -> Result<()> {
-    let vault = &mut ctx.accounts.vault;
+/// Send `amount` of the accumulated entry/exit fees to the vault's fee
+/// recipient (the recipient itself or a Treasury role holder)
+pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    let is_recipient = authority == ctx.accounts.vault.treasury;
+    let is_treasury = ctx
+        .accounts
+        .authority_role
+        .as_ref()
+        .is_some_and(|role| role.has_role(ROLE_TREASURY));
+    if !is_recipient && !is_treasury {
+        return Err(VaultError::Unauthorized.into());
+    }
 
-    // Check if there are any fees to withdraw
-    if vault.accumulated_fees == 0 {
+    // A split table routes fees through `distribute_fees` instead
+    if !ctx.accounts.vault.fee_splits.is_empty() {
+        return Err(VaultError::FeeSplitActive.into());
+    }
+
+    if amount == 0 || amount > ctx.accounts.vault.accumulated_fees {
         return Err(VaultError::InvalidAmount.into());
     }
 
-    let fee_amount = vault.accumulated_fees;
-
-    // Transfer fees to treasury
-    system_program::transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            system_program::Transfer {
-                from: ctx.accounts.vault_account.to_account_info(),
-                to: ctx.accounts.treasury.to_account_info(),
-            },
-        ),
-        fee_amount,
+    // Fees are paid from idle custody only, never from assets owed or
+    // reserved for withdraw requests
+    if amount > ctx.accounts.asset_accounts().fee_liquidity()? {
+        return Err(VaultError::InsufficientLiquidity.into());
+    }
+
+    // Transfer fees to the recipient, signed by the vault SOL PDA
+    ctx.accounts.asset_accounts().transfer_out(
+        &ctx.accounts.treasury.to_account_info(),
+        ctx.accounts.treasury_token_account.as_ref(),
+        amount,
     )?;
 
-    // Reset accumulated fees
-    vault.accumulated_fees = 0;
+    let vault = &mut ctx.accounts.vault;
+    vault.accumulated_fees -= amount;
+    msg!("Withdrew {} in fees, {} remaining", amount, vault.accumulated_fees);
 
     Ok(())
 }
@@ -38,14 +54,74 @@ pub struct WithdrawFees<'info> {
     #[account(mut)]
     pub vault: Account<'info, Vault>,
 
+    /// Role authority for the vault (carries the pause flags)
+    #[account(
+        seeds = [b"role_authority", vault.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = role_authority.bump,
+        constraint = !role_authority.is_paused(PAUSE_WITHDRAWALS | PAUSE_ADMIN) @ VaultError::EmergencyPaused
+    )]
+    pub role_authority: Account<'info, RoleAuthority>,
+
     /// Vault SOL account (PDA that holds funds)
-    #[account(mut)]
-    pub vault_account: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault_sol", vault.key().as_ref()],
+        bump = vault.vault_account_bump
+    )]
+    pub vault_account: SystemAccount<'info>,
 
-    /// Treasury wallet that receives the fees
-    #[account(mut)]
-    pub treasury: Signer<'info>,
+    /// Underlying asset mint (token vaults only)
+    #[account(address = vault.asset_mint)]
+    pub asset_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account holding the underlying asset (token vaults only)
+    #[account(
+        mut,
+        seeds = [b"vault_token", vault.key().as_ref()],
+        bump = vault.vault_token_bump
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Fee recipient or a Treasury role holder
+    pub authority: Signer<'info>,
+
+    /// Authority's role for this vault (needed unless it is the fee recipient)
+    #[account(
+        seeds = [b"user_role", vault.key().as_ref(), authority.key().as_ref()],
+        seeds::program = user_management::ID,
+        bump = authority_role.bump
+    )]
+    pub authority_role: Option<Account<'info, UserRole>>,
+
+    /// Fee recipient configured on the vault
+    #[account(mut, address = vault.treasury @ VaultError::Unauthorized)]
+    pub treasury: SystemAccount<'info>,
+
+    /// Fee recipient's token account receiving the fees (token vaults only)
+    #[account(
+        mut,
+        token::mint = vault.asset_mint,
+        token::authority = treasury
+    )]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
 
     /// System program
     pub system_program: Program<'info, System>,
+
+    /// SPL token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WithdrawFees<'info> {
+    pub fn asset_accounts(&self) -> AssetAccounts<'_, 'info> {
+        AssetAccounts {
+            vault: &self.vault,
+            vault_account: &self.vault_account,
+            asset_mint: self.asset_mint.as_ref(),
+            vault_token_account: self.vault_token_account.as_ref(),
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+        }
+    }
 }
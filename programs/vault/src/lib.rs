@@ -1,9 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 
-mod errors;
-mod state;
-mod rbac_helper;
+pub mod errors;
+pub mod state;
+pub mod assets;
 mod instructions;
 
 use errors::VaultError;
@@ -20,27 +20,104 @@ pub mod vault {
         instructions::initialize(ctx)
     }
 
-    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-        instructions::deposit(ctx, amount)
+    pub fn initialize_token_vault(ctx: Context<InitializeTokenVault>) -> Result<()> {
+        instructions::initialize_token_vault(ctx)
     }
 
-    pub fn withdraw(ctx: Context<Withdraw>, shares: u64) -> Result<()> {
-        instructions::withdraw(ctx, shares)
+    pub fn deposit(ctx: Context<Deposit>, amount: u64, min_shares_out: u64) -> Result<()> {
+        instructions::deposit(ctx, amount, min_shares_out)
     }
 
-    pub fn harvest(ctx: Context<Harvest>, yield_amount: u64) -> Result<()> {
-        instructions::harvest(ctx, yield_amount)
+    pub fn mint_shares(ctx: Context<Deposit>, shares: u64, max_assets_in: u64) -> Result<()> {
+        instructions::mint_shares(ctx, shares, max_assets_in)
     }
 
-    pub fn withdraw_fees(ctx: Context<WithdrawFees>) -> Result<()> {
-        instructions::withdraw_fees(ctx)
+    pub fn withdraw(ctx: Context<Withdraw>, shares: u64, min_assets_out: u64) -> Result<()> {
+        instructions::withdraw(ctx, shares, min_assets_out)
     }
 
-    pub fn update_settings(
-        ctx: Context<UpdateSettings>,
-        strategy_allocation: Option<u8>,
+    pub fn withdraw_assets(
+        ctx: Context<Withdraw>,
+        amount: u64,
+        max_shares_burned: u64,
     ) -> Result<()> {
-        instructions::update_settings(ctx, strategy_allocation)
+        instructions::withdraw_assets(ctx, amount, max_shares_burned)
+    }
+
+    pub fn request_withdraw(ctx: Context<RequestWithdraw>, shares: u64) -> Result<()> {
+        instructions::request_withdraw(ctx, shares)
+    }
+
+    pub fn process_withdraw_queue<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessWithdrawQueue<'info>>,
+    ) -> Result<()> {
+        instructions::process_withdraw_queue(ctx)
+    }
+
+    pub fn claim_withdraw(ctx: Context<ClaimWithdraw>) -> Result<()> {
+        instructions::claim_withdraw(ctx)
+    }
+
+    pub fn harvest<'info>(ctx: Context<'_, '_, 'info, 'info, Harvest<'info>>) -> Result<()> {
+        instructions::harvest(ctx)
+    }
+
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+        instructions::withdraw_fees(ctx, amount)
+    }
+
+    pub fn distribute_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeFees<'info>>,
+    ) -> Result<()> {
+        instructions::distribute_fees(ctx)
+    }
+
+    pub fn deploy_to_strategy(ctx: Context<DeployToStrategy>, amount: u64) -> Result<()> {
+        instructions::deploy_to_strategy(ctx, amount)
+    }
+
+    pub fn recall_from_strategy(
+        ctx: Context<RecallFromStrategy>,
+        amount: u64,
+        principal: u64,
+    ) -> Result<()> {
+        instructions::recall_from_strategy(ctx, amount, principal)
+    }
+
+    pub fn update_settings(ctx: Context<UpdateSettings>, settings: SettingsUpdate) -> Result<()> {
+        instructions::update_settings(ctx, settings)
+    }
+
+    pub fn apply_fee_config(ctx: Context<ApplyFeeConfig>) -> Result<()> {
+        instructions::apply_fee_config(ctx)
+    }
+
+    pub fn apply_exit_policy(ctx: Context<ApplyExitPolicy>) -> Result<()> {
+        instructions::apply_exit_policy(ctx)
+    }
+
+    pub fn preview_deposit(ctx: Context<Preview>, amount: u64) -> Result<u64> {
+        instructions::preview_deposit(ctx, amount)
+    }
+
+    pub fn preview_mint(ctx: Context<Preview>, shares: u64) -> Result<u64> {
+        instructions::preview_mint(ctx, shares)
+    }
+
+    pub fn preview_withdraw(ctx: Context<Preview>, amount: u64) -> Result<u64> {
+        instructions::preview_withdraw(ctx, amount)
+    }
+
+    pub fn preview_redeem(ctx: Context<Preview>, shares: u64) -> Result<u64> {
+        instructions::preview_redeem(ctx, shares)
+    }
+
+    pub fn max_deposit(ctx: Context<MaxDeposit>) -> Result<u64> {
+        instructions::max_deposit(ctx)
+    }
+
+    pub fn max_withdraw(ctx: Context<MaxWithdraw>) -> Result<u64> {
+        instructions::max_withdraw(ctx)
     }
 }
 
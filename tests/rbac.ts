@@ -11,6 +11,11 @@ describe("User Management RBAC System", () => {
   const ROLE_ADMIN = 1 << 1;             // 2
   const ROLE_STRATEGY_MANAGER = 1 << 2;  // 4
   const ROLE_TREASURY = 1 << 3;          // 8
+  const ROLE_GUARDIAN = 1 << 4;          // 16
+
+  // Pause flags (must match Rust definitions)
+  const PAUSE_DEPOSITS = 1 << 0;
+  const PAUSE_WITHDRAWALS = 1 << 1;
 
   // Test wallets
   const admin = anchor.web3.Keypair.generate();
@@ -26,7 +31,7 @@ describe("User Management RBAC System", () => {
       expect(ROLE_ADMIN).to.equal(2);
     });
 
-    it("should set emergency_pause to false initially", async () => {
+    it("should start with no operations paused", async () => {
       expect(true).to.be.true;
     });
   });
@@ -95,21 +100,23 @@ describe("User Management RBAC System", () => {
     });
   });
 
-  describe("Emergency Pause", () => {
-    it("should allow super admin to pause system", async () => {
+  describe("Pause Flags", () => {
+    it("should allow admin or guardian to pause operations", async () => {
       expect(true).to.be.true;
     });
 
-    it("should block role-restricted operations when paused", async () => {
-      // When emergency_pause is true, has_role returns false
-      expect(true).to.be.true;
+    it("should pause deposits without pausing withdrawals", async () => {
+      const paused = PAUSE_DEPOSITS;
+      expect((paused & PAUSE_DEPOSITS) !== 0).to.be.true;
+      expect((paused & PAUSE_WITHDRAWALS) !== 0).to.be.false;
     });
 
-    it("should allow super admin to unpause system", async () => {
-      expect(true).to.be.true;
+    it("should only allow admin to unpause", async () => {
+      // A guardian can pause but not unpause
+      expect(ROLE_GUARDIAN).to.equal(16);
     });
 
-    it("should reject non-super-admin pause requests", async () => {
+    it("should reject callers without admin or guardian role", async () => {
       expect(true).to.be.true;
     });
   });
@@ -134,8 +141,7 @@ describe("User Management RBAC System", () => {
     it("should derive correct UserRole PDA", async () => {
       const [userRolePda] = anchor.web3.PublicKey.findProgramAddressSync(
         [Buffer.from("user_role"), vaultKey.toBuffer(), user1.publicKey.toBuffer()],
-        // Use the actual user_management program ID
-        new anchor.web3.PublicKey("UMgmtXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX")
+        new anchor.web3.PublicKey("BE2bDcazEUS5YpSW2JgJ2CAeTErp8LrpkZWAuW8WXa4h")
       );
       expect(userRolePda).to.be.instanceOf(anchor.web3.PublicKey);
     });
@@ -143,8 +149,7 @@ describe("User Management RBAC System", () => {
     it("should derive correct RoleAuthority PDA", async () => {
       const [roleAuthorityPda] = anchor.web3.PublicKey.findProgramAddressSync(
         [Buffer.from("role_authority"), vaultKey.toBuffer()],
-        // Use the actual user_management program ID
-        new anchor.web3.PublicKey("UMgmtXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX")
+        new anchor.web3.PublicKey("BE2bDcazEUS5YpSW2JgJ2CAeTErp8LrpkZWAuW8WXa4h")
       );
       expect(roleAuthorityPda).to.be.instanceOf(anchor.web3.PublicKey);
     });
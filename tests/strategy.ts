@@ -1,6 +1,8 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Strategy } from "../target/types/strategy";
+import { Vault } from "../target/types/vault";
+import { UserManagement } from "../target/types/user_management";
 import { assert } from "chai";
 
 describe("Strategy", () => {
@@ -8,31 +10,80 @@ describe("Strategy", () => {
   anchor.setProvider(provider);
 
   const program = anchor.workspace.Strategy as Program<Strategy>;
-  let strategyState: anchor.web3.Keypair;
+  const vaultProgram = anchor.workspace.Vault as Program<Vault>;
+  const userManagement = anchor.workspace.UserManagement as Program<UserManagement>;
+  const admin = provider.wallet.publicKey;
+  let vault: anchor.web3.Keypair;
+  let strategyState: anchor.web3.PublicKey;
+  let adminRole: anchor.web3.PublicKey;
 
   before(async () => {
-    strategyState = anchor.web3.Keypair.generate();
+    // The strategy serves a vault, whose admin holds the Admin role
+    vault = anchor.web3.Keypair.generate();
+    const [vaultAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_sol"), vault.publicKey.toBuffer()],
+      vaultProgram.programId
+    );
+    const [shareMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("share_mint"), vault.publicKey.toBuffer()],
+      vaultProgram.programId
+    );
+    await vaultProgram.methods
+      .initialize()
+      .accounts({
+        vault: vault.publicKey,
+        vaultAccount: vaultAccount,
+        shareMint: shareMint,
+        admin: admin,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .signers([vault])
+      .rpc();
+
+    const [roleAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("role_authority"), vault.publicKey.toBuffer()],
+      userManagement.programId
+    );
+    [adminRole] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user_role"), vault.publicKey.toBuffer(), admin.toBuffer()],
+      userManagement.programId
+    );
+    await userManagement.methods
+      .initializeRoleAuthority()
+      .accounts({
+        vault: vault.publicKey,
+        roleAuthority: roleAuthority,
+        userRole: adminRole,
+        admin: admin,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    [strategyState] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("strategy"), vault.publicKey.toBuffer()],
+      program.programId
+    );
   });
 
   it("initializes strategy state", async () => {
-    const admin = provider.wallet.publicKey;
-    const vault = anchor.web3.Keypair.generate().publicKey;
     const rebalanceThreshold = 10;
 
     const tx = await program.methods
       .initialize(rebalanceThreshold)
       .accounts({
-        strategyState: strategyState.publicKey,
-        vault: vault,
+        strategyState: strategyState,
+        vault: vault.publicKey,
         admin: admin,
+        adminRole: adminRole,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
-      .signers([strategyState])
       .rpc();
 
     console.log("Initialize tx:", tx);
 
-    const state = await program.account.strategyState.fetch(strategyState.publicKey);
+    const state = await program.account.strategyState.fetch(strategyState);
+    assert.ok(state.vault.equals(vault.publicKey));
     assert.equal(state.numOpportunities, 0);
     assert.equal(state.rebalanceThreshold, rebalanceThreshold);
     assert.equal(state.deployedValue.toNumber(), 0);
@@ -10,12 +10,17 @@ describe("Vault", () => {
   const program = anchor.workspace.Vault as Program<Vault>;
   let vault: anchor.web3.Keypair;
   let vaultAccount: anchor.web3.PublicKey;
+  let shareMint: anchor.web3.PublicKey;
 
   before(async () => {
     // Create vault account
     vault = anchor.web3.Keypair.generate();
-    vaultAccount = anchor.web3.PublicKey.createProgramAddressSync(
-      [Buffer.from("vault_pda")],
+    [vaultAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_sol"), vault.publicKey.toBuffer()],
+      program.programId
+    );
+    [shareMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("share_mint"), vault.publicKey.toBuffer()],
       program.programId
     );
   });
@@ -27,8 +32,11 @@ describe("Vault", () => {
       .initialize()
       .accounts({
         vault: vault.publicKey,
+        vaultAccount: vaultAccount,
+        shareMint: shareMint,
         admin: admin,
         systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
       })
       .signers([vault])
       .rpc();
@@ -39,6 +47,7 @@ describe("Vault", () => {
     assert.equal(vaultAccount.totalAssets.toNumber(), 0);
     assert.equal(vaultAccount.totalShares.toNumber(), 0);
     assert.equal(vaultAccount.numUsers.toNumber(), 0);
+    assert.ok(vaultAccount.shareMint.equals(shareMint));
   });
 
   it("calculates share price correctly", async () => {